@@ -1,7 +1,7 @@
 use std::{env::current_dir, fs::create_dir_all};
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
-use vendetta_markets_parimutuel_market::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use vendetta_markets_parimutuel_market::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -12,4 +12,5 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(SudoMsg), &out_dir);
 }
@@ -1,25 +1,64 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coin, to_json_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Response, StdResult, Uint128,
+    coin, to_json_binary, to_json_vec, Addr, Attribute, BankMsg, Binary, CosmosMsg, Decimal, Deps,
+    DepsMut, Empty, Env, Event, MessageInfo, Order, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
+use cw721::{Cw721QueryMsg, OwnerOfResponse, TokensResponse};
+use cw721_base::ExecuteMsg as Cw721ExecuteMsg;
+use cw_storage_plus::Bound;
+use pyth_sdk_cw::{query_price_feed, PriceIdentifier};
+use sha2::{Digest, Sha256};
 
 use crate::{
-    calculate_parimutuel_winnings,
+    calculate_fee, calculate_handicap_result, calculate_settled_bets, calculate_totals_result,
+    calculate_virtual_winnings, calculate_weighted_stake,
     error::ContractError,
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    handicap_line_in_quarters,
+    lmsr::{calculate_buy_cost, calculate_prices, calculate_sell_proceeds, calculate_subsidy},
+    msg::{ComplianceQueryMsg, ExecuteMsg, InstantiateMsg, IsAllowedResponse, QueryMsg, SudoMsg},
+    price_to_decimal,
     state::{
-        Config, Market, MarketResult, Status, CLAIMS, CONFIG, MARKET, POOL_AWAY, POOL_DRAW,
-        POOL_HOME, TOTAL_AWAY, TOTAL_DRAW, TOTAL_HOME,
+        add_to_pool, add_to_weighted_pool, bet_ledger, default_resolution_deadline,
+        load_address_pool, load_outcomes, load_pool, load_total, load_total_pool,
+        load_weighted_pool, load_weighted_total, record_bet, remove_from_pool, seed_totals,
+        transfer_pool, Bet, BetSlip, Commitment, Config, FeeModel, House, KeyType, Market,
+        MarketResult, MarketType, PendingFee, Proposal, Resolution, ScoreResult, SealedBets,
+        SeedPolicy, SellOrder, Status, UnrevealedPolicy, Vote, ASSOCIATED, BETTORS, BLOCKLIST,
+        CLAIMS, COMMITMENTS, CONFIG, DISTRIBUTION_CURSOR, FLAT_FEE_REWARDS, FORFEITED, HOUSE,
+        LISTED, MARKET, NET_SPENT, ODDS, PENDING_FEE, POOL_SCORE, POTENTIAL_PAYOUTS, PROPOSAL,
+        REFERRAL_CLAIMS, REFERRAL_REWARDS, REFERRED_STAKES, REFERRED_VOLUME, REFERRERS,
+        SELL_ORDERS, SELL_ORDER_COUNT, SLIPS, SLIP_CLAIMS, SLIP_COUNT, TOTAL_AWAY, TOTAL_DRAW,
+        TOTAL_HOME, TOTAL_OVER, TOTAL_PAYOUTS, TOTAL_REFERRED_STAKES, TOTAL_REFERRED_VOLUME,
+        TOTAL_SCORE, TOTAL_UNDER, UNREVEALED, VOTES,
     },
-    TotalBets,
+    Attestation, BetHistoryPage, Liability, OutcomeLiability, OutcomeOdds, OutcomePrice,
+    ReferralInfo, ScoreBet, SealedBet, SlipInfo, TotalBets,
 };
 
+#[cfg(feature = "tokenfactory")]
+use crate::tokenfactory::{position_denom, position_subdenom, TokenFactoryMsg};
+
+#[cfg(feature = "tokenfactory")]
+pub type ContractMsg = TokenFactoryMsg;
+#[cfg(not(feature = "tokenfactory"))]
+pub type ContractMsg = cosmwasm_std::Empty;
+
+pub type Response = cosmwasm_std::Response<ContractMsg>;
+
 pub const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+pub const MAX_FEE_BPS: u64 = 1000;
+/// Delay before a fee increase can apply
+pub const FEE_TIMELOCK: u64 = 24 * 60 * 60;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+/// Ledger entries examined by a single bet history query
+const MAX_BET_SCAN: usize = 300;
+
 const ADMIN_ADDRESS: &str = "neutron15yhlj25av4fkw6s8qwnzerp490pkxmn9094g7r";
 const TREASURY_ADDRESS: &str = "neutron12v9pqx602k3rzm5hf4jewepl8na4x89ja4td24";
 
@@ -37,14 +76,214 @@ pub fn instantiate(
     // set_contract_version(deps.storage, format!("crates.io:{CONTRACT_NAME}"), CONTRACT_VERSION)?;
     set_contract_version(deps.storage, format!("crates.io:{CONTRACT_NAME}"), CONTRACT_VERSION)?;
 
+    if msg.fee_bps > MAX_FEE_BPS {
+        return Err(ContractError::InvalidFee(MAX_FEE_BPS));
+    }
+
+    let slip_collection = match msg.slip_collection {
+        // Positions minted as denoms cannot also be held as bet slips
+        Some(_) if cfg!(feature = "tokenfactory") => {
+            return Err(ContractError::PositionsTokenized {});
+        }
+        // LMSR shares are bought without a slip, so they could never be claimed
+        Some(_) if matches!(msg.market_type, Some(MarketType::LMSR { .. })) => {
+            return Err(ContractError::UnsupportedMarketType {});
+        }
+        Some(slip_collection) => Some(deps.api.addr_validate(&slip_collection)?),
+        None => None,
+    };
+
+    let resolution = msg.resolution.unwrap_or_default();
+    match resolution {
+        Resolution::ADMIN => {}
+        Resolution::OPTIMISTIC {
+            bond,
+            ref arbiter,
+            ..
+        } => {
+            if bond.is_zero() {
+                return Err(ContractError::InvalidAmount {});
+            }
+            deps.api.addr_validate(arbiter.as_str())?;
+        }
+        Resolution::COMMITTEE {
+            ref resolvers,
+            threshold,
+            ..
+        } => {
+            let mut unique_resolvers: Vec<&Addr> = vec![];
+            for resolver in resolvers {
+                deps.api.addr_validate(resolver.as_str())?;
+                if unique_resolvers.contains(&resolver) {
+                    return Err(ContractError::InvalidResolvers {});
+                }
+                unique_resolvers.push(resolver);
+            }
+
+            if threshold == 0 || threshold as usize > resolvers.len() {
+                return Err(ContractError::InvalidResolvers {});
+            }
+        }
+        Resolution::ATTESTATION {
+            ref providers,
+        } => {
+            if providers.is_empty() {
+                return Err(ContractError::InvalidResolvers {});
+            }
+        }
+    }
+
+    let compliance_contract = match msg.compliance_contract {
+        Some(compliance_contract) => Some(deps.api.addr_validate(&compliance_contract)?),
+        None => None,
+    };
+
+    let referral_share_bps = msg.referral_share_bps.unwrap_or_default();
+    if referral_share_bps > 10000 {
+        return Err(ContractError::InvalidReferralShare {});
+    }
+
+    let resolution_deadline = msg.resolution_deadline.unwrap_or_else(default_resolution_deadline);
+    // The market must be scoreable for a while before anyone can cancel it
+    if resolution_deadline <= 30 * 60 {
+        return Err(ContractError::InvalidResolutionDeadline {});
+    }
+
+    let fee_model = msg.fee_model.unwrap_or_default();
+    if let FeeModel::FLAT_PER_BET {
+        amount,
+    } = fee_model
+    {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidAmount {});
+        }
+    }
+
+    let mut time_weight_attributes: Vec<Attribute> = vec![];
+    if let Some(time_weight) = &msg.time_weight {
+        if time_weight.max_bonus.is_zero() || time_weight.decay_period == 0 {
+            return Err(ContractError::InvalidTimeWeight {});
+        }
+
+        // Late winners could get back less than their stake, which the profit
+        // based fee models rule out
+        if matches!(fee_model, FeeModel::PROFIT_ONLY | FeeModel::LOSING_POOL) {
+            return Err(ContractError::InvalidTimeWeight {});
+        }
+
+        // Only parimutuel pools are shared out by stake
+        if matches!(msg.market_type, Some(MarketType::FIXED_ODDS) | Some(MarketType::LMSR { .. })) {
+            return Err(ContractError::UnsupportedMarketType {});
+        }
+
+        // Positions held as denoms or bet slips carry no weight with them
+        if cfg!(feature = "tokenfactory") || slip_collection.is_some() {
+            return Err(ContractError::PositionsTokenized {});
+        }
+
+        time_weight_attributes.push(Attribute::new("max_bonus", time_weight.max_bonus.to_string()));
+        time_weight_attributes
+            .push(Attribute::new("decay_period", time_weight.decay_period.to_string()));
+    }
+
     let state = Config {
         admin_addr: Addr::unchecked(ADMIN_ADDRESS),
         treasury_addr: Addr::unchecked(TREASURY_ADDRESS),
         fee_bps: msg.fee_bps,
+        fee_model,
         denom: msg.denom,
+        slip_collection,
+        transfers_disabled: false,
+        resolution,
+        resolution_deadline,
+        time_weight: msg.time_weight,
+        seed: msg.seed,
+        referral_share_bps,
+        compliance_contract,
+        sealed_bets: msg.sealed_bets,
     };
     CONFIG.save(deps.storage, &state)?;
 
+    let market_type = msg.market_type.unwrap_or_default();
+    let mut line_attributes: Vec<Attribute> = vec![];
+    match market_type {
+        MarketType::WINNER => {}
+        MarketType::FIXED_ODDS => HOUSE.save(deps.storage, &House::default())?,
+        MarketType::LMSR {
+            liquidity,
+        } => {
+            if liquidity.is_zero() {
+                return Err(ContractError::InvalidLiquidity {});
+            }
+
+            // The creator funds the worst case loss of the market maker
+            let outcomes = if msg.is_drawable {
+                3
+            } else {
+                2
+            };
+            let subsidy = calculate_subsidy(liquidity.u128(), outcomes)?;
+            let funds = cw_utils::must_pay(&info, &state.denom)
+                .map_err(|_| ContractError::InsufficientSubsidy(subsidy.u128()))?;
+            if funds < subsidy {
+                return Err(ContractError::InsufficientSubsidy(subsidy.u128()));
+            }
+
+            HOUSE.save(
+                deps.storage,
+                &House {
+                    liquidity: funds.u128(),
+                    ..House::default()
+                },
+            )?;
+            line_attributes.push(Attribute::new("liquidity", liquidity.to_string()));
+            line_attributes.push(Attribute::new("subsidy", funds.to_string()));
+        }
+        MarketType::TOTALS {
+            line,
+        } => {
+            // Lines are quoted in half units, e.g. 2.5 maps or 26 rounds
+            let double_line = line * Decimal::from_ratio(2_u128, 1_u128);
+            if line.is_zero() || double_line.floor() != double_line {
+                return Err(ContractError::InvalidLine {});
+            }
+            line_attributes.push(Attribute::new("line", line.to_string()));
+        }
+        MarketType::HANDICAP {
+            line,
+        } => {
+            // Lines are quoted in quarter units, e.g. -1.5 or -0.25 (Asian handicap)
+            if handicap_line_in_quarters(line).is_none() {
+                return Err(ContractError::InvalidLine {});
+            }
+            line_attributes.push(Attribute::new("line", line.to_string()));
+        }
+        MarketType::CORRECT_SCORE {
+            max_outcomes,
+        } => {
+            if max_outcomes == 0 {
+                return Err(ContractError::InvalidMaxOutcomes {});
+            }
+            line_attributes.push(Attribute::new("max_outcomes", max_outcomes.to_string()));
+        }
+        MarketType::PRICE_FEED {
+            ref pyth_contract,
+            ref price_feed_id,
+            strike,
+            ..
+        } => {
+            deps.api.addr_validate(pyth_contract.as_str())?;
+            if PriceIdentifier::from_hex(price_feed_id).is_err() {
+                return Err(ContractError::InvalidPriceFeed {});
+            }
+            if strike.is_zero() {
+                return Err(ContractError::InvalidPrice {});
+            }
+            line_attributes.push(Attribute::new("price_feed_id", price_feed_id));
+            line_attributes.push(Attribute::new("strike", strike.to_string()));
+        }
+    }
+
     let market = Market {
         id: msg.id,
         label: msg.label,
@@ -54,14 +293,110 @@ pub fn instantiate(
         status: Status::ACTIVE,
         result: None,
         is_drawable: msg.is_drawable,
+        market_type,
+        virtual_weights: msg.virtual_weights.unwrap_or_default(),
     };
+
+    let mut virtual_attributes: Vec<Attribute> = vec![];
+    if !market.virtual_weights.is_empty() {
+        // Virtual stake only makes sense on pools with a fixed set of outcomes
+        let outcomes = market.outcomes();
+        if outcomes.is_empty() || !market.is_parimutuel() {
+            return Err(ContractError::UnsupportedMarketType {});
+        }
+
+        let mut weighted_outcomes: Vec<&MarketResult> = vec![];
+        for weight in market.virtual_weights.iter() {
+            if !outcomes.contains(&weight.result) || weighted_outcomes.contains(&&weight.result) {
+                return Err(ContractError::InvalidOutcome {});
+            }
+            if weight.amount.is_zero() {
+                return Err(ContractError::InvalidAmount {});
+            }
+            weighted_outcomes.push(&weight.result);
+
+            virtual_attributes.push(Attribute::new(
+                format!("virtual_{}", weight.result.to_string().to_lowercase()),
+                weight.amount.to_string(),
+            ));
+        }
+    }
+
     MARKET.save(deps.storage, &market)?;
 
     TOTAL_HOME.save(deps.storage, &0)?;
     TOTAL_AWAY.save(deps.storage, &0)?;
     TOTAL_DRAW.save(deps.storage, &0)?;
+    TOTAL_OVER.save(deps.storage, &0)?;
+    TOTAL_UNDER.save(deps.storage, &0)?;
+    TOTAL_REFERRED_VOLUME.save(deps.storage, &0)?;
+
+    let mut sealed_attributes: Vec<Attribute> = vec![];
+    if let Some(sealed_bets) = &state.sealed_bets {
+        // Only pools with a fixed set of outcomes, so a reveal is never refused
+        if !market.is_parimutuel() || matches!(market.market_type, MarketType::CORRECT_SCORE { .. })
+        {
+            return Err(ContractError::UnsupportedMarketType {});
+        }
+
+        // Bets all revealed after the cutoff carry no time weight, nor any
+        // odds to record on a slip
+        if state.time_weight.is_some() {
+            return Err(ContractError::InvalidTimeWeight {});
+        }
+        if state.slip_collection.is_some() {
+            return Err(ContractError::PositionsTokenized {});
+        }
+
+        // Every commitment is settled by the time the market can be scored
+        if sealed_bets.reveal_window == 0
+            || reveal_deadline(&market, sealed_bets) > scoreable_timestamp(&market)
+        {
+            return Err(ContractError::InvalidRevealWindow {});
+        }
+
+        if let UnrevealedPolicy::REFUND {
+            penalty_bps,
+        } = sealed_bets.unrevealed
+        {
+            if penalty_bps > 10000 {
+                return Err(ContractError::InvalidPenalty {});
+            }
+            sealed_attributes.push(Attribute::new("penalty_bps", penalty_bps.to_string()));
+        }
+
+        UNREVEALED.save(deps.storage, &0)?;
+
+        sealed_attributes
+            .push(Attribute::new("reveal_window", sealed_bets.reveal_window.to_string()));
+        sealed_attributes.push(Attribute::new("unrevealed", sealed_bets.unrevealed.to_string()));
+    }
+
+    let mut seed_attributes: Vec<Attribute> = vec![];
+    if let Some(seed) = &state.seed {
+        // Only pools with a fixed set of outcomes can be seeded up front
+        if !market.is_parimutuel() || matches!(market.market_type, MarketType::CORRECT_SCORE { .. })
+        {
+            return Err(ContractError::UnsupportedMarketType {});
+        }
+
+        if seed.amount.is_zero() {
+            return Err(ContractError::InvalidAmount {});
+        }
+
+        let funds = cw_utils::must_pay(&info, &state.denom);
+        if funds.map_or(true, |funds| funds.u128() != market.seed_stake(&state)) {
+            return Err(ContractError::PaymentError {});
+        }
+
+        seed_totals(deps.storage, &state, &market, seed.amount.u128())?;
+
+        seed_attributes.push(Attribute::new("seed_amount", seed.amount.to_string()));
+        seed_attributes.push(Attribute::new("seed_policy", seed.policy.to_string()));
+    }
 
     Ok(Response::new()
+        .add_messages(create_position_denoms(&market))
         .add_attribute("protocol", "vendetta-markets")
         .add_attribute("market_type", "parimutuel")
         .add_attribute("action", "create_market")
@@ -72,15 +407,142 @@ pub fn instantiate(
         .add_attribute("away_team", market.away_team)
         .add_attribute("start_timestamp", market.start_timestamp.to_string())
         .add_attribute("is_drawable", msg.is_drawable.to_string())
+        .add_attribute("type", market.market_type.to_string())
+        .add_attribute("resolution", state.resolution.to_string())
+        .add_attribute("resolution_deadline", state.resolution_deadline.to_string())
+        .add_attribute("referral_share_bps", state.referral_share_bps.to_string())
+        .add_attribute("fee_model", state.fee_model.to_string())
+        .add_attributes(line_attributes)
+        .add_attributes(time_weight_attributes)
+        .add_attributes(seed_attributes)
+        .add_attributes(virtual_attributes)
+        .add_attributes(sealed_attributes)
         .add_attribute("status", Status::ACTIVE.to_string()))
 }
 
+// SUDO
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::LockBetting {} => sudo_lock_betting(deps, env),
+        SudoMsg::CancelOverdue {} => sudo_cancel_overdue(deps, env),
+        SudoMsg::DistributePayouts {
+            limit,
+        } => sudo_distribute_payouts(deps, env, limit),
+    }
+}
+
+/// Locks betting once the cutoff is reached, a no-op before it or if already locked
+fn sudo_lock_betting(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let market = MARKET.load(deps.storage)?;
+
+    if market.status != Status::ACTIVE || betting_cutoff(&market) > env.block.time.seconds() {
+        return Ok(Response::new());
+    }
+
+    let market = MARKET.update(deps.storage, |mut market| -> Result<_, ContractError> {
+        market.status = Status::LOCKED;
+        Ok(market)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "lock_betting")
+        .add_attribute("sender", env.contract.address)
+        .add_attribute("status", Status::LOCKED.to_string())
+        .add_attributes(totals_attributes(deps.storage, &market)?))
+}
+
+/// Cancels the market once past its resolution deadline, a no-op otherwise
+fn sudo_cancel_overdue(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if !market.is_unresolved() || env.block.time.seconds() < resolution_deadline(&config, &market) {
+        return Ok(Response::new());
+    }
+
+    force_cancel(deps, config, env.contract.address)
+}
+
+/// Pays out the next batch of addresses that have not claimed yet, picking up
+/// where the previous batch left off. Claimed addresses are skipped, so it is
+/// safe to run alongside regular claims and once everyone is paid it is a no-op.
+/// Like a regular claim, an address owed nothing is not marked as claimed.
+fn sudo_distribute_payouts(deps: DepsMut, env: Env, limit: u32) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if market.is_unresolved() {
+        return Ok(Response::new());
+    }
+
+    // Tokenized positions are paid to whoever holds them, which is unknown here
+    if (cfg!(feature = "tokenfactory") && market.is_parimutuel())
+        || config.slip_collection.is_some()
+    {
+        return Err(ContractError::PositionsTokenized {});
+    }
+
+    let limit = limit.min(MAX_LIMIT) as usize;
+    let start = DISTRIBUTION_CURSOR.may_load(deps.storage)?.map(Bound::exclusive);
+    let bettors = BETTORS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+    let mut events: Vec<Event> = vec![];
+    for addr in bettors.iter() {
+        if CLAIMS.has(deps.storage, addr.clone()) {
+            continue;
+        }
+
+        let payout = calculate_payout(deps.storage, &config, &market, addr)?;
+        if payout == 0 {
+            continue;
+        }
+        CLAIMS.save(deps.storage, addr.clone(), &true)?;
+
+        messages.push(
+            BankMsg::Send {
+                to_address: addr.to_string(),
+                amount: vec![coin(payout, config.denom.clone())],
+            }
+            .into(),
+        );
+        events.push(Event::new("claim_winnings").add_attributes(claim_attributes(
+            &env.contract.address,
+            addr,
+            payout,
+        )));
+    }
+
+    if let Some(last) = bettors.last() {
+        DISTRIBUTION_CURSOR.save(deps.storage, last)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(events)
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "distribute_payouts")
+        .add_attribute("sender", env.contract.address)
+        .add_attribute("processed", bettors.len().to_string()))
+}
+
 // QUERIES
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => query_config(deps),
+        QueryMsg::PendingFee {} => query_pending_fee(deps),
+        QueryMsg::Proposal {} => query_proposal(deps),
+        QueryMsg::Votes {} => query_votes(deps),
         QueryMsg::Market {} => query_market(deps),
         QueryMsg::Bets {} => query_bets(deps),
         QueryMsg::BetsByAddress {
@@ -90,6 +552,47 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             address,
             result,
         } => query_estimate_winnings(deps, address, result),
+        QueryMsg::ScoreBets {} => query_score_bets(deps),
+        QueryMsg::ScoreBetsByAddress {
+            address,
+        } => query_score_bets_by_address(deps, address),
+        QueryMsg::SlipsByOwner {
+            owner,
+            start_after,
+            limit,
+        } => query_slips_by_owner(deps, owner, start_after, limit),
+        QueryMsg::Liability {} => query_liability(deps),
+        QueryMsg::Odds {} => query_odds(deps),
+        QueryMsg::Price {} => query_price(deps),
+        QueryMsg::SellOrder {
+            order_id,
+        } => query_sell_order(deps, order_id),
+        QueryMsg::SellOrders {
+            start_after,
+            limit,
+        } => query_sell_orders(deps, start_after, limit),
+        QueryMsg::Referral {
+            referrer,
+        } => query_referral(deps, referrer),
+        QueryMsg::Commitments {
+            address,
+        } => query_commitments(deps, address),
+        QueryMsg::BetHistory {
+            address,
+            result,
+            start_time,
+            end_time,
+            start_after,
+            limit,
+        } => query_bet_history(deps, address, result, start_time, end_time, start_after, limit),
+        QueryMsg::Blocklist {
+            start_after,
+            limit,
+        } => query_blocklist(deps, start_after, limit),
+        QueryMsg::AssociatedAddresses {
+            start_after,
+            limit,
+        } => query_associated_addresses(deps, start_after, limit),
     }
 }
 
@@ -98,6 +601,26 @@ fn query_config(deps: Deps) -> StdResult<Binary> {
     to_json_binary(&config)
 }
 
+fn query_pending_fee(deps: Deps) -> StdResult<Binary> {
+    let pending_fee = PENDING_FEE.may_load(deps.storage)?;
+    to_json_binary(&pending_fee)
+}
+
+fn query_proposal(deps: Deps) -> StdResult<Binary> {
+    let proposal = PROPOSAL.may_load(deps.storage)?;
+    to_json_binary(&proposal)
+}
+
+/// Every vote cast so far, so conflicting votes are visible
+fn query_votes(deps: Deps) -> StdResult<Binary> {
+    let votes = VOTES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<Vote>>>()?;
+
+    to_json_binary(&votes)
+}
+
 fn query_market(deps: Deps) -> StdResult<Binary> {
     let market = MARKET.load(deps.storage)?;
     to_json_binary(&market)
@@ -105,77 +628,376 @@ fn query_market(deps: Deps) -> StdResult<Binary> {
 
 fn query_bets(deps: Deps) -> StdResult<Binary> {
     let totals = TotalBets {
-        total_home: TOTAL_HOME.load(deps.storage)?,
-        total_away: TOTAL_AWAY.load(deps.storage)?,
-        total_draw: TOTAL_DRAW.load(deps.storage)?,
+        total_home: load_total(deps.storage, &MarketResult::HOME)?,
+        total_away: load_total(deps.storage, &MarketResult::AWAY)?,
+        total_draw: load_total(deps.storage, &MarketResult::DRAW)?,
+        total_over: load_total(deps.storage, &MarketResult::OVER)?,
+        total_under: load_total(deps.storage, &MarketResult::UNDER)?,
     };
     to_json_binary(&totals)
 }
 
 fn query_bets_by_address(deps: Deps, address: Addr) -> StdResult<Binary> {
-    let total_home = if POOL_HOME.has(deps.storage, address.clone()) {
-        POOL_HOME.load(deps.storage, address.clone())?
-    } else {
-        0
+    let totals = TotalBets {
+        total_home: load_pool(deps.storage, &MarketResult::HOME, &address)?,
+        total_away: load_pool(deps.storage, &MarketResult::AWAY, &address)?,
+        total_draw: load_pool(deps.storage, &MarketResult::DRAW, &address)?,
+        total_over: load_pool(deps.storage, &MarketResult::OVER, &address)?,
+        total_under: load_pool(deps.storage, &MarketResult::UNDER, &address)?,
     };
+    to_json_binary(&totals)
+}
 
-    let total_away = if POOL_AWAY.has(deps.storage, address.clone()) {
-        POOL_AWAY.load(deps.storage, address.clone())?
-    } else {
-        0
-    };
+fn query_score_bets(deps: Deps) -> StdResult<Binary> {
+    let score_bets = TOTAL_SCORE
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let ((home, away), total) = item?;
+            Ok(ScoreBet {
+                home,
+                away,
+                total,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&score_bets)
+}
 
-    let total_draw = if POOL_DRAW.has(deps.storage, address.clone()) {
-        POOL_DRAW.load(deps.storage, address)?
-    } else {
-        0
-    };
+fn query_score_bets_by_address(deps: Deps, address: Addr) -> StdResult<Binary> {
+    let score_bets = POOL_SCORE
+        .sub_prefix(address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let ((home, away), total) = item?;
+            Ok(ScoreBet {
+                home,
+                away,
+                total,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&score_bets)
+}
 
-    let totals = TotalBets {
-        total_home,
-        total_away,
-        total_draw,
+/// Bet slips of this market held by an owner, paginated over the owner's
+/// tokens in the collection
+fn query_slips_by_owner(
+    deps: Deps,
+    owner: Addr,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let slip_collection = match config.slip_collection {
+        Some(slip_collection) => slip_collection,
+        None => return to_json_binary(&Vec::<SlipInfo>::new()),
     };
-    to_json_binary(&totals)
+
+    let tokens: TokensResponse = deps.querier.query_wasm_smart(
+        slip_collection,
+        &Cw721QueryMsg::Tokens {
+            owner: owner.to_string(),
+            start_after,
+            limit,
+        },
+    )?;
+
+    let mut slips: Vec<SlipInfo> = vec![];
+    for token_id in tokens.tokens {
+        if let Some(slip) = SLIPS.may_load(deps.storage, token_id.clone())? {
+            let claimed = SLIP_CLAIMS.has(deps.storage, token_id.clone());
+            slips.push(SlipInfo {
+                token_id,
+                slip,
+                claimed,
+            });
+        }
+    }
+
+    to_json_binary(&slips)
 }
 
-fn query_estimate_winnings(deps: Deps, address: Addr, result: MarketResult) -> StdResult<Binary> {
-    let total_home = TOTAL_HOME.load(deps.storage)?;
-    let total_away = TOTAL_AWAY.load(deps.storage)?;
-    let total_draw = TOTAL_DRAW.load(deps.storage)?;
+fn query_liability(deps: Deps) -> StdResult<Binary> {
+    let market = MARKET.load(deps.storage)?;
+    let house = HOUSE.may_load(deps.storage)?.unwrap_or_default();
+    let total_stakes = load_total_pool(deps.storage, &market)?;
+
+    let mut outcomes = vec![];
+    for outcome in market.outcomes() {
+        let potential_payout =
+            TOTAL_PAYOUTS.may_load(deps.storage, outcome.to_string())?.unwrap_or_default();
+        outcomes.push(OutcomeLiability {
+            odds: ODDS.may_load(deps.storage, outcome.to_string())?,
+            potential_payout,
+            liability: potential_payout.saturating_sub(total_stakes),
+            result: outcome,
+        });
+    }
 
-    let addr_pool_home = if POOL_HOME.has(deps.storage, address.clone()) {
-        POOL_HOME.load(deps.storage, address.clone())?
-    } else {
-        0
-    };
+    to_json_binary(&Liability {
+        house_liquidity: house.liquidity,
+        total_stakes,
+        outcomes,
+    })
+}
 
-    let addr_pool_away = if POOL_AWAY.has(deps.storage, address.clone()) {
-        POOL_AWAY.load(deps.storage, address.clone())?
-    } else {
-        0
-    };
+/// Odds currently offered on each outcome, implied by the pools and virtual
+/// weights on parimutuel markets
+fn query_odds(deps: Deps) -> StdResult<Binary> {
+    let market = MARKET.load(deps.storage)?;
 
-    let addr_pool_draw = if POOL_DRAW.has(deps.storage, address.clone()) {
-        POOL_DRAW.load(deps.storage, address)?
-    } else {
-        0
+    let mut odds = vec![];
+    match market.market_type {
+        MarketType::FIXED_ODDS => {
+            for outcome in market.outcomes() {
+                if let Some(outcome_odds) = ODDS.may_load(deps.storage, outcome.to_string())? {
+                    odds.push(OutcomeOdds {
+                        result: outcome,
+                        odds: outcome_odds,
+                    });
+                }
+            }
+        }
+        MarketType::LMSR {
+            ..
+        } => return Err(StdError::generic_err("Unsupported market type")),
+        _ => {
+            let total_bets =
+                load_total_pool(deps.storage, &market)? + market.total_virtual_weight();
+            for outcome in load_outcomes(deps.storage, &market)? {
+                let team_bets =
+                    load_total(deps.storage, &outcome)? + market.virtual_weight(&outcome);
+                if team_bets == 0 {
+                    continue;
+                }
+
+                odds.push(OutcomeOdds {
+                    odds: Decimal::from_ratio(total_bets, team_bets),
+                    result: outcome,
+                });
+            }
+        }
+    }
+
+    to_json_binary(&odds)
+}
+
+fn query_referral(deps: Deps, referrer: Addr) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let referred_volume =
+        REFERRED_VOLUME.may_load(deps.storage, referrer.clone())?.unwrap_or_default();
+    let claimed = REFERRAL_CLAIMS.has(deps.storage, referrer.clone());
+
+    // Until the market is scored, the rewards on a fee on the whole pool are
+    // estimated as if every stake settles. A share of the flat fees is held as
+    // the bets are placed, while a fee on the losing pool or on profit is only
+    // known once the market is scored.
+    let flat_fee = matches!(config.fee_model, FeeModel::FLAT_PER_BET { .. });
+    let pending_rewards = match market.status {
+        _ if claimed => 0,
+        _ if flat_fee || market.status == Status::CLOSED => {
+            calculate_referral_rewards(deps.storage, &config, &market, &referrer)?
+        }
+        Status::ACTIVE | Status::LOCKED if config.fee_model == FeeModel::GROSS_POOL => {
+            Uint128::from(calculate_fee(config.fee_bps, referred_volume))
+                .multiply_ratio(config.referral_share_bps, 10000_u128)
+                .u128()
+        }
+        _ => 0,
     };
 
-    let addr_bets = match result {
-        MarketResult::HOME => addr_pool_home,
-        MarketResult::AWAY => addr_pool_away,
-        MarketResult::DRAW => addr_pool_draw,
+    to_json_binary(&ReferralInfo {
+        referrer,
+        referred_volume,
+        pending_rewards,
+        claimed,
+    })
+}
+
+fn query_commitments(deps: Deps, address: Addr) -> StdResult<Binary> {
+    let commitments = COMMITMENTS
+        .prefix(address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, commitment)| commitment))
+        .collect::<StdResult<Vec<Commitment>>>()?;
+
+    to_json_binary(&commitments)
+}
+
+/// Bets in the ledger, oldest first, narrowed down by bettor, outcome and
+/// placement time
+fn query_bet_history(
+    deps: Deps,
+    address: Option<Addr>,
+    result: Option<MarketResult>,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let ledger = bet_ledger();
+    let entries: Box<dyn Iterator<Item = StdResult<(u64, Bet)>>> = match (&address, &result) {
+        (Some(address), _) => Box::new(ledger.idx.bettor.prefix(address.clone()).range(
+            deps.storage,
+            start,
+            None,
+            Order::Ascending,
+        )),
+        (None, Some(result)) => Box::new(ledger.idx.outcome.prefix(result.to_string()).range(
+            deps.storage,
+            start,
+            None,
+            Order::Ascending,
+        )),
+        (None, None) => Box::new(ledger.range(deps.storage, start, None, Order::Ascending)),
     };
 
-    let team_bets = match result {
-        MarketResult::HOME => total_home,
-        MarketResult::AWAY => total_away,
-        MarketResult::DRAW => total_draw,
+    // Sealed bets are recorded as of their commitment once revealed, so ids do
+    // not follow timestamps and entries have to be filtered one by one
+    let mut bets: Vec<Bet> = vec![];
+    let mut last_examined: Option<u64> = None;
+    let mut next_start_after: Option<u64> = None;
+    for (examined, entry) in entries.enumerate() {
+        if bets.len() == limit || examined == MAX_BET_SCAN {
+            next_start_after = last_examined;
+            break;
+        }
+
+        let (id, bet) = entry?;
+        last_examined = Some(id);
+
+        let is_result = match &result {
+            Some(result) => bet.result == *result,
+            None => true,
+        };
+        if is_result
+            && bet.timestamp >= start_time.unwrap_or_default()
+            && bet.timestamp < end_time.unwrap_or(u64::MAX)
+        {
+            bets.push(bet);
+        }
+    }
+
+    to_json_binary(&BetHistoryPage {
+        bets,
+        next_start_after,
+    })
+}
+
+fn query_blocklist(deps: Deps, start_after: Option<Addr>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let blocked = BLOCKLIST
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    to_json_binary(&blocked)
+}
+
+fn query_associated_addresses(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let associated = ASSOCIATED
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    to_json_binary(&associated)
+}
+
+fn query_price(deps: Deps) -> StdResult<Binary> {
+    let market = MARKET.load(deps.storage)?;
+
+    let liquidity = match market.market_type {
+        MarketType::LMSR {
+            liquidity,
+        } => liquidity,
+        _ => return Err(StdError::generic_err("Market is not an LMSR market")),
     };
 
-    let estimate =
-        calculate_parimutuel_winnings(total_home + total_away + total_draw, team_bets, addr_bets);
+    let outcomes = market.outcomes();
+    let quantities = load_quantities(deps.storage, &outcomes)?;
+    let prices = calculate_prices(&quantities, liquidity.u128())?;
+
+    let prices = outcomes
+        .into_iter()
+        .zip(prices)
+        .map(|(result, price)| {
+            Ok(OutcomePrice {
+                result,
+                price: price.try_into().map_err(|_| StdError::generic_err("Price out of range"))?,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_json_binary(&prices)
+}
+
+fn query_sell_order(deps: Deps, order_id: u64) -> StdResult<Binary> {
+    let order = SELL_ORDERS.load(deps.storage, order_id)?;
+    to_json_binary(&order)
+}
+
+fn query_sell_orders(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let orders = SELL_ORDERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<SellOrder>>>()?;
+
+    to_json_binary(&orders)
+}
+
+fn query_estimate_winnings(deps: Deps, address: Addr, result: MarketResult) -> StdResult<Binary> {
+    let market = MARKET.load(deps.storage)?;
+
+    // Fixed odds payouts are locked in when the bet is placed, while each LMSR
+    // share pays out one unit if its outcome wins
+    match market.market_type {
+        MarketType::FIXED_ODDS => {
+            let estimate = POTENTIAL_PAYOUTS
+                .may_load(deps.storage, (address, result.to_string()))?
+                .unwrap_or_default();
+            return to_json_binary(&estimate);
+        }
+        MarketType::LMSR {
+            ..
+        } => return to_json_binary(&load_pool(deps.storage, &result, &address)?),
+        _ => {}
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let addr_bets = load_weighted_pool(deps.storage, &config, &result, &address)?;
+    let team_bets = load_winning_total(deps.storage, &config, &result)?;
+    let total_bets = load_total_pool(deps.storage, &market)?;
+
+    let estimate = calculate_virtual_winnings(
+        total_bets,
+        team_bets,
+        addr_bets,
+        market.total_virtual_weight(),
+        market.virtual_weight(&result),
+    );
 
     to_json_binary(&estimate)
 }
@@ -193,10 +1015,29 @@ pub fn execute(
         ExecuteMsg::PlaceBet {
             result,
             receiver,
-        } => execute_place_bet(deps, env, info, result, receiver),
+            referrer,
+        } => execute_place_bet(deps, env, info, result, receiver, referrer),
         ExecuteMsg::ClaimWinnings {
             receiver,
-        } => execute_claim_winnings(deps, info, receiver),
+        } => {
+            #[cfg(feature = "tokenfactory")]
+            if MARKET.load(deps.storage)?.is_parimutuel() {
+                return execute_claim_positions(deps, env, info, receiver);
+            }
+            execute_claim_winnings(deps, info, receiver)
+        }
+        ExecuteMsg::ClaimSlips {
+            token_ids,
+        } => execute_claim_slips(deps, info, token_ids),
+        ExecuteMsg::ClaimReferralRewards {} => execute_claim_referral_rewards(deps, info),
+        ExecuteMsg::CommitBet {
+            commitment,
+        } => execute_commit_bet(deps, env, info, commitment),
+        ExecuteMsg::RevealBet {
+            result,
+            salt,
+        } => execute_reveal_bet(deps, env, info, result, salt),
+        ExecuteMsg::ClaimCommitments {} => execute_claim_commitments(deps, env, info),
         ExecuteMsg::Update {
             start_timestamp,
         } => execute_update(deps, info, start_timestamp),
@@ -204,6 +1045,74 @@ pub fn execute(
             result,
         } => execute_score(deps, env, info, result),
         ExecuteMsg::Cancel {} => execute_cancel(deps, info),
+        ExecuteMsg::DepositLiquidity {} => execute_deposit_liquidity(deps, info),
+        ExecuteMsg::UpdateOdds {
+            odds,
+        } => execute_update_odds(deps, info, odds),
+        ExecuteMsg::WithdrawLiquidity {} => execute_withdraw_liquidity(deps, info),
+        ExecuteMsg::Buy {
+            result,
+            shares,
+            max_cost,
+        } => execute_buy(deps, env, info, result, shares, max_cost),
+        ExecuteMsg::Sell {
+            result,
+            shares,
+            min_proceeds,
+        } => execute_sell(deps, env, info, result, shares, min_proceeds),
+        ExecuteMsg::CreateSellOrder {
+            result,
+            amount,
+            price,
+        } => execute_create_sell_order(deps, info, result, amount, price),
+        ExecuteMsg::FillSellOrder {
+            order_id,
+            amount,
+        } => execute_fill_sell_order(deps, info, order_id, amount),
+        ExecuteMsg::CancelSellOrder {
+            order_id,
+        } => execute_cancel_sell_order(deps, info, order_id),
+        ExecuteMsg::TransferPosition {
+            outcome,
+            amount,
+            recipient,
+        } => execute_transfer_position(deps, info, outcome, amount, recipient),
+        ExecuteMsg::Propose {
+            result,
+        } => execute_propose(deps, env, info, result),
+        ExecuteMsg::Dispute {} => execute_dispute(deps, env, info),
+        ExecuteMsg::Finalize {} => execute_finalize(deps, env, info),
+        ExecuteMsg::Arbitrate {
+            result,
+        } => execute_arbitrate(deps, env, info, result),
+        ExecuteMsg::VoteResult {
+            result,
+        } => execute_vote_result(deps, env, info, result),
+        ExecuteMsg::ForceCancel {} => execute_force_cancel(deps, env, info),
+        ExecuteMsg::ScoreWithAttestation {
+            result,
+            market_id,
+            timestamp,
+            signature,
+        } => {
+            execute_score_with_attestation(deps, env, info, result, market_id, timestamp, signature)
+        }
+        ExecuteMsg::SetTransfersDisabled {
+            disabled,
+        } => execute_set_transfers_disabled(deps, info, disabled),
+        ExecuteMsg::UpdateConfig {
+            fee_bps,
+            treasury_addr,
+            denom,
+        } => execute_update_config(deps, env, info, fee_bps, treasury_addr, denom),
+        ExecuteMsg::UpdateBlocklist {
+            add,
+            remove,
+        } => execute_update_blocklist(deps, info, add, remove),
+        ExecuteMsg::UpdateAssociatedAddresses {
+            add,
+            remove,
+        } => execute_update_associated_addresses(deps, info, add, remove),
     }
 }
 
@@ -213,6 +1122,7 @@ fn execute_place_bet(
     info: MessageInfo,
     result: MarketResult,
     receiver: Option<Addr>,
+    referrer: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let market = MARKET.load(deps.storage)?;
@@ -222,62 +1132,179 @@ fn execute_place_bet(
         None => info.sender.clone(),
     };
 
-    if !market.is_drawable && result == MarketResult::DRAW {
-        return Err(ContractError::MarketNotDrawable {});
+    check_allowed(deps.as_ref(), &config, &info.sender)?;
+    check_not_privileged(deps.storage, &config, &info.sender)?;
+    if addr != info.sender {
+        check_allowed(deps.as_ref(), &config, &addr)?;
+        check_not_privileged(deps.storage, &config, &addr)?;
+    }
+
+    let referrer = match referrer {
+        Some(referrer) => Some(deps.api.addr_validate(referrer.as_str())?),
+        None => None,
+    };
+    // Neither the bettor nor whoever places the bet for them can refer it
+    if referrer.as_ref() == Some(&addr) || referrer.as_ref() == Some(&info.sender) {
+        return Err(ContractError::InvalidReferrer {});
+    }
+
+    if config.sealed_bets.is_some() {
+        return Err(ContractError::BetsMustBeSealed {});
     }
 
+    validate_result(deps.storage, &market, &result)?;
+
     if market.status != Status::ACTIVE {
         return Err(ContractError::MarketNotActive {});
     }
 
     // Bets are accepted up until 5 minutes before the start of the match
-    if market.start_timestamp - 5 * 60 < env.block.time.seconds() {
+    if betting_cutoff(&market) < env.block.time.seconds() {
         return Err(ContractError::BetsNotAccepted {});
     }
 
+    let (config, fee_attributes) = apply_pending_fee(deps.storage, &env, config)?;
+
+    // A bettor keeps the first referrer given for them, bets placed before it
+    // was given stay unreferred
+    if let Some(referrer) = referrer {
+        if !REFERRERS.has(deps.storage, addr.clone()) {
+            REFERRERS.save(deps.storage, addr.clone(), &referrer)?;
+        }
+    }
+    let referrer = REFERRERS.may_load(deps.storage, addr.clone())?;
+
     let bet_amount = cw_utils::must_pay(&info, &config.denom);
     if bet_amount.is_err() {
         return Err(ContractError::PaymentError {});
     }
-    let bet_amount = bet_amount.unwrap();
-
-    match result {
-        MarketResult::HOME => {
-            if !POOL_HOME.has(deps.storage, addr.clone()) {
-                POOL_HOME.save(deps.storage, addr.clone(), &0)?;
-            }
+    let (bet_amount, referral_fee, fee_messages) =
+        deduct_flat_fee(&config, bet_amount.unwrap(), referrer.is_some())?;
+
+    let is_new_outcome = match result {
+        MarketResult::SCORE {
+            home,
+            away,
+        } => !TOTAL_SCORE.has(deps.storage, (home, away)),
+        _ => false,
+    };
 
-            POOL_HOME.update(deps.storage, addr.clone(), |pool| -> StdResult<_> {
-                Ok(pool.unwrap() + bet_amount.u128())
+    add_to_pool(deps.storage, &result, &addr, bet_amount.u128())?;
+
+    let mut bet_attributes: Vec<Attribute> = vec![];
+
+    if let Some(referrer) = referrer {
+        REFERRED_VOLUME.update(deps.storage, referrer.clone(), |volume| -> StdResult<_> {
+            Ok(volume.unwrap_or_default() + bet_amount.u128())
+        })?;
+        TOTAL_REFERRED_VOLUME
+            .update(deps.storage, |volume| -> StdResult<_> { Ok(volume + bet_amount.u128()) })?;
+        REFERRED_STAKES.update(
+            deps.storage,
+            (referrer.clone(), result.to_string()),
+            |stake| -> StdResult<_> { Ok(stake.unwrap_or_default() + bet_amount.u128()) },
+        )?;
+        TOTAL_REFERRED_STAKES.update(
+            deps.storage,
+            result.to_string(),
+            |stake| -> StdResult<_> { Ok(stake.unwrap_or_default() + bet_amount.u128()) },
+        )?;
+        if !referral_fee.is_zero() {
+            FLAT_FEE_REWARDS.update(deps.storage, referrer.clone(), |rewards| -> StdResult<_> {
+                Ok(rewards.unwrap_or_default() + referral_fee.u128())
             })?;
-            TOTAL_HOME
-                .update(deps.storage, |total| -> StdResult<_> { Ok(total + bet_amount.u128()) })?
         }
-        MarketResult::AWAY => {
-            if !POOL_AWAY.has(deps.storage, addr.clone()) {
-                POOL_AWAY.save(deps.storage, addr.clone(), &0)?;
-            }
 
-            POOL_AWAY.update(deps.storage, addr.clone(), |pool| -> StdResult<_> {
-                Ok(pool.unwrap() + bet_amount.u128())
-            })?;
-            TOTAL_AWAY
-                .update(deps.storage, |total| -> StdResult<_> { Ok(total + bet_amount.u128()) })?
-        }
-        MarketResult::DRAW => {
-            if !POOL_DRAW.has(deps.storage, addr.clone()) {
-                POOL_DRAW.save(deps.storage, addr.clone(), &0)?;
-            }
+        bet_attributes.push(Attribute::new("referrer", referrer));
+    }
+    if let Some(time_weight) = &config.time_weight {
+        let cutoff = betting_cutoff(&market);
+        let weighted_stake = calculate_weighted_stake(
+            bet_amount.u128(),
+            time_weight.max_bonus,
+            time_weight.decay_period,
+            cutoff - env.block.time.seconds(),
+        );
+        add_to_weighted_pool(deps.storage, &result, &addr, weighted_stake)?;
 
-            POOL_DRAW.update(deps.storage, addr.clone(), |pool| -> StdResult<_> {
-                Ok(pool.unwrap() + bet_amount.u128())
+        bet_attributes.push(Attribute::new("weighted_stake", weighted_stake.to_string()));
+    }
+    let mut locked_odds: Option<Decimal> = None;
+    if market.market_type == MarketType::FIXED_ODDS {
+        let odds =
+            ODDS.may_load(deps.storage, result.to_string())?.ok_or(ContractError::OddsNotSet {})?;
+        let potential_payout = (bet_amount * odds).u128();
+
+        POTENTIAL_PAYOUTS.update(
+            deps.storage,
+            (addr.clone(), result.to_string()),
+            |payout| -> StdResult<_> { Ok(payout.unwrap_or_default() + potential_payout) },
+        )?;
+        let total_payouts =
+            TOTAL_PAYOUTS.update(deps.storage, result.to_string(), |payouts| -> StdResult<_> {
+                Ok(payouts.unwrap_or_default() + potential_payout)
             })?;
-            TOTAL_DRAW
-                .update(deps.storage, |total| -> StdResult<_> { Ok(total + bet_amount.u128()) })?
+
+        // The house must be able to cover the payouts of the outcome if it wins
+        let house = HOUSE.load(deps.storage)?;
+        let total_stakes = load_total_pool(deps.storage, &market)?;
+        if total_payouts > house.liquidity + total_stakes {
+            return Err(ContractError::MaxLiabilityExceeded {});
         }
+
+        bet_attributes.push(Attribute::new("odds", odds.to_string()));
+        bet_attributes.push(Attribute::new("potential_payout", potential_payout.to_string()));
+        locked_odds = Some(odds);
+    }
+
+    let odds = match locked_odds {
+        Some(odds) => odds,
+        None => implied_odds(deps.storage, &market, &result)?,
     };
+    let bet_id = record_bet(
+        deps.storage,
+        &addr,
+        &result,
+        bet_amount.u128(),
+        env.block.time.seconds(),
+        odds,
+    )?;
+    bet_attributes.push(Attribute::new("bet_id", bet_id.to_string()));
+
+    let mut messages = mint_position(&env, &market, &result, bet_amount, &addr, is_new_outcome);
+    messages.extend(fee_messages);
+    if let Some(slip_collection) = &config.slip_collection {
+        let slip_count = SLIP_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        SLIP_COUNT.save(deps.storage, &slip_count)?;
+
+        let token_id = format!("{}-{}", market.id, slip_count);
+        let slip = BetSlip {
+            market_id: market.id.clone(),
+            result: result.clone(),
+            stake: bet_amount.u128(),
+            timestamp: env.block.time.seconds(),
+            odds,
+        };
+        SLIPS.save(deps.storage, token_id.clone(), &slip)?;
+
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: slip_collection.to_string(),
+                msg: to_json_binary(&Cw721ExecuteMsg::<BetSlip, Empty>::Mint {
+                    token_id: token_id.clone(),
+                    owner: addr.to_string(),
+                    token_uri: None,
+                    extension: slip,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+        bet_attributes.push(Attribute::new("slip_id", token_id));
+    }
 
     Ok(Response::new()
+        .add_messages(messages)
         .add_attribute("protocol", "vendetta-markets")
         .add_attribute("market_type", "parimutuel")
         .add_attribute("action", "place_bet")
@@ -285,251 +1312,2580 @@ fn execute_place_bet(
         .add_attribute("receiver", addr)
         .add_attribute("bet_amount", bet_amount.to_string())
         .add_attribute("result", result.to_string())
-        .add_attribute("total_home", TOTAL_HOME.load(deps.storage)?.to_string())
-        .add_attribute("total_away", TOTAL_AWAY.load(deps.storage)?.to_string())
-        .add_attribute("total_draw", TOTAL_DRAW.load(deps.storage)?.to_string()))
+        .add_attributes(bet_attributes)
+        .add_attributes(fee_attributes)
+        .add_attributes(totals_attributes(deps.storage, &market)?))
 }
 
-fn execute_claim_winnings(
+/// Odds implied by the pools of a parimutuel market right after a bet
+fn implied_odds(
+    storage: &dyn Storage,
+    market: &Market,
+    result: &MarketResult,
+) -> StdResult<Decimal> {
+    Ok(Decimal::from_ratio(
+        load_total_pool(storage, market)? + market.total_virtual_weight(),
+        load_total(storage, result)? + market.virtual_weight(result),
+    ))
+}
+
+/// Rejects results the market cannot be bet on
+fn validate_result(
+    storage: &dyn Storage,
+    market: &Market,
+    result: &MarketResult,
+) -> Result<(), ContractError> {
+    if !market.is_drawable && *result == MarketResult::DRAW {
+        return Err(ContractError::MarketNotDrawable {});
+    }
+
+    match (&market.market_type, result) {
+        (
+            MarketType::CORRECT_SCORE {
+                max_outcomes,
+            },
+            MarketResult::SCORE {
+                home,
+                away,
+            },
+        ) => {
+            // Betting on a score without a pool yet creates that outcome
+            if !TOTAL_SCORE.has(storage, (*home, *away))
+                && TOTAL_SCORE.keys(storage, None, None, Order::Ascending).count()
+                    >= *max_outcomes as usize
+            {
+                return Err(ContractError::MaxOutcomesReached {});
+            }
+        }
+        (
+            MarketType::LMSR {
+                ..
+            },
+            _,
+        ) => return Err(ContractError::UnsupportedMarketType {}),
+        _ => {
+            if !market.outcomes().contains(result) {
+                return Err(ContractError::InvalidOutcome {});
+            }
+        }
+    }
+
+    Ok(())
+}
+/// Takes a flat fee out of the bet itself, returning the rest to stake, the
+/// share of the fee held for the referrer of a referred bet and the message
+/// sending the rest of the fee to the treasury
+fn deduct_flat_fee(
+    config: &Config,
+    bet_amount: Uint128,
+    referred: bool,
+) -> Result<(Uint128, Uint128, Vec<CosmosMsg<ContractMsg>>), ContractError> {
+    let amount = match config.fee_model {
+        FeeModel::FLAT_PER_BET {
+            amount,
+        } => amount,
+        _ => return Ok((bet_amount, Uint128::zero(), vec![])),
+    };
+
+    if bet_amount <= amount {
+        return Err(ContractError::PaymentError {});
+    }
+
+    let mut referral_fee = Uint128::zero();
+    if referred {
+        referral_fee = amount.multiply_ratio(config.referral_share_bps, 10000_u128);
+    }
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+    let treasury_fee = amount - referral_fee;
+    if !treasury_fee.is_zero() {
+        messages.push(
+            BankMsg::Send {
+                to_address: config.treasury_addr.to_string(),
+                amount: vec![coin(treasury_fee.u128(), config.denom.clone())],
+            }
+            .into(),
+        );
+    }
+
+    Ok((bet_amount - amount, referral_fee, messages))
+}
+
+fn execute_commit_bet(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    receiver: Option<Addr>,
+    commitment: Binary,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let market = MARKET.load(deps.storage)?;
 
-    let addr = match receiver {
-        Some(receiver) => deps.api.addr_validate(receiver.as_str())?,
-        None => info.sender.clone(),
-    };
+    if config.sealed_bets.is_none() {
+        return Err(ContractError::SealedBetsNotEnabled {});
+    }
 
-    if market.status == Status::ACTIVE {
-        return Err(ContractError::MarketNotClosed {});
+    check_allowed(deps.as_ref(), &config, &info.sender)?;
+    check_not_privileged(deps.storage, &config, &info.sender)?;
+
+    // A sha256 hash
+    if commitment.len() != 32 {
+        return Err(ContractError::InvalidCommitment {});
     }
 
-    if CLAIMS.has(deps.storage, addr.clone()) {
-        return Err(ContractError::ClaimAlreadyMade {});
+    if market.status != Status::ACTIVE {
+        return Err(ContractError::MarketNotActive {});
     }
 
-    let payout;
+    if betting_cutoff(&market) < env.block.time.seconds() {
+        return Err(ContractError::BetsNotAccepted {});
+    }
 
-    let addr_pool_home = if POOL_HOME.has(deps.storage, addr.clone()) {
-        POOL_HOME.load(deps.storage, addr.clone())?
-    } else {
-        0
-    };
+    let (config, fee_attributes) = apply_pending_fee(deps.storage, &env, config)?;
 
-    let addr_pool_away = if POOL_AWAY.has(deps.storage, addr.clone()) {
-        POOL_AWAY.load(deps.storage, addr.clone())?
-    } else {
-        0
-    };
+    let bet_amount = cw_utils::must_pay(&info, &config.denom);
+    if bet_amount.is_err() {
+        return Err(ContractError::PaymentError {});
+    }
+    let (bet_amount, _, fee_messages) = deduct_flat_fee(&config, bet_amount.unwrap(), false)?;
 
-    let addr_pool_draw = if POOL_DRAW.has(deps.storage, addr.clone()) {
-        POOL_DRAW.load(deps.storage, addr.clone())?
-    } else {
-        0
-    };
+    let key = (info.sender.clone(), commitment.to_base64());
+    if COMMITMENTS.has(deps.storage, key.clone()) {
+        return Err(ContractError::CommitmentAlreadyMade {});
+    }
+    COMMITMENTS.save(
+        deps.storage,
+        key,
+        &Commitment {
+            commitment: commitment.clone(),
+            amount: bet_amount,
+            timestamp: env.block.time.seconds(),
+        },
+    )?;
+    UNREVEALED.update(deps.storage, |unrevealed| -> StdResult<_> {
+        Ok(unrevealed + bet_amount.u128())
+    })?;
 
-    if market.status == Status::CANCELLED {
-        payout = addr_pool_home + addr_pool_away + addr_pool_draw;
-    } else {
-        let bet_amount = match market.result {
-            Some(MarketResult::HOME) => addr_pool_home,
-            Some(MarketResult::AWAY) => addr_pool_away,
-            Some(MarketResult::DRAW) => addr_pool_draw,
-            None => 0,
-        };
+    Ok(Response::new()
+        .add_messages(fee_messages)
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "commit_bet")
+        .add_attribute("sender", info.sender)
+        .add_attribute("commitment", commitment.to_base64())
+        .add_attribute("bet_amount", bet_amount.to_string())
+        .add_attributes(fee_attributes))
+}
 
-        let total_home = TOTAL_HOME.load(deps.storage)?;
-        let total_away = TOTAL_AWAY.load(deps.storage)?;
-        let total_draw = TOTAL_DRAW.load(deps.storage)?;
+fn execute_reveal_bet(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    result: MarketResult,
+    salt: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
 
-        let team_bets = match market.result {
-            Some(MarketResult::HOME) => total_home,
-            Some(MarketResult::AWAY) => total_away,
-            Some(MarketResult::DRAW) => total_draw,
-            None => 0,
-        };
+    let sealed_bets = config.sealed_bets.as_ref().ok_or(ContractError::SealedBetsNotEnabled {})?;
 
-        let mut fee_amount = Uint128::zero();
-        if config.fee_bps > 0 {
-            fee_amount = Uint128::from(total_home + total_away + total_draw)
-                .multiply_ratio(Uint128::from(config.fee_bps), Uint128::from(10000_u128));
-        }
+    if !market.is_unresolved() {
+        return Err(ContractError::MarketNotActive {});
+    }
 
-        payout = calculate_parimutuel_winnings(
-            total_home + total_away + total_draw - fee_amount.u128(),
-            team_bets,
-            bet_amount,
-        );
+    // Nothing is revealed while bets are still being committed
+    let now = env.block.time.seconds();
+    if now <= betting_cutoff(&market) || now > reveal_deadline(&market, sealed_bets) {
+        return Err(ContractError::RevealWindowNotOpen {});
     }
 
-    let mut messages: Vec<CosmosMsg> = vec![];
+    validate_result(deps.storage, &market, &result)?;
 
-    if payout > 0 {
-        messages.push(
-            BankMsg::Send {
-                to_address: addr.to_string(),
-                amount: vec![coin(payout, config.denom)],
-            }
-            .into(),
-        );
-    } else {
-        return Err(ContractError::NoWinnings {});
+    let commitment = SealedBet {
+        bettor: info.sender.to_string(),
+        result: result.clone(),
+        salt,
     }
+    .commitment()?;
+    let key = (info.sender.clone(), commitment.to_base64());
+    let sealed_bet = COMMITMENTS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    COMMITMENTS.remove(deps.storage, key);
+    UNREVEALED.update(deps.storage, |unrevealed| -> StdResult<_> {
+        Ok(unrevealed - sealed_bet.amount.u128())
+    })?;
 
-    CLAIMS.save(deps.storage, addr.clone(), &true)?;
+    add_to_pool(deps.storage, &result, &info.sender, sealed_bet.amount.u128())?;
+
+    // The bet is recorded as placed when committed, at the odds it was revealed at
+    let odds = implied_odds(deps.storage, &market, &result)?;
+    let bet_id = record_bet(
+        deps.storage,
+        &info.sender,
+        &result,
+        sealed_bet.amount.u128(),
+        sealed_bet.timestamp,
+        odds,
+    )?;
 
     Ok(Response::new()
-        .add_messages(messages)
+        .add_messages(mint_position(&env, &market, &result, sealed_bet.amount, &info.sender, false))
         .add_attribute("protocol", "vendetta-markets")
         .add_attribute("market_type", "parimutuel")
-        .add_attribute("action", "claim_winnings")
+        .add_attribute("action", "reveal_bet")
         .add_attribute("sender", info.sender)
-        .add_attribute("receiver", addr)
-        .add_attribute("payout", payout.to_string()))
+        .add_attribute("commitment", commitment.to_base64())
+        .add_attribute("bet_amount", sealed_bet.amount.to_string())
+        .add_attribute("result", result.to_string())
+        .add_attribute("bet_id", bet_id.to_string())
+        .add_attributes(totals_attributes(deps.storage, &market)?))
 }
 
-fn execute_update(
+/// Refunds the sealed bets of the sender that were never revealed, in full if
+/// the market was cancelled
+fn execute_claim_commitments(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    start_timestamp: u64,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let market = MARKET.load(deps.storage)?;
 
-    if info.sender != config.admin_addr {
-        return Err(ContractError::Unauthorized {});
+    let sealed_bets = config.sealed_bets.as_ref().ok_or(ContractError::SealedBetsNotEnabled {})?;
+
+    let penalty_bps = match (&market.status, &sealed_bets.unrevealed) {
+        (Status::CANCELLED, _) => 0,
+        (
+            _,
+            UnrevealedPolicy::REFUND {
+                penalty_bps,
+            },
+        ) => {
+            if env.block.time.seconds() <= reveal_deadline(&market, sealed_bets) {
+                return Err(ContractError::RevealWindowOpen {});
+            }
+            *penalty_bps
+        }
+        (_, UnrevealedPolicy::FORFEIT) => return Err(ContractError::NoWinnings {}),
+    };
+
+    let commitments = COMMITMENTS
+        .prefix(info.sender.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, Commitment)>>>()?;
+    if commitments.is_empty() {
+        return Err(ContractError::NoWinnings {});
     }
 
-    if market.status != Status::ACTIVE {
-        return Err(ContractError::MarketNotActive {});
+    let mut amount = Uint128::zero();
+    for (key, commitment) in commitments {
+        COMMITMENTS.remove(deps.storage, (info.sender.clone(), key));
+        amount += commitment.amount;
     }
+    UNREVEALED
+        .update(deps.storage, |unrevealed| -> StdResult<_> { Ok(unrevealed - amount.u128()) })?;
 
-    let mut market = MARKET.load(deps.storage)?;
-    market.start_timestamp = start_timestamp;
+    let penalty = amount.multiply_ratio(penalty_bps, 10000_u128);
+    let refund = amount - penalty;
 
-    MARKET.save(deps.storage, &market)?;
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+    if !refund.is_zero() {
+        messages.push(
+            BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![coin(refund.u128(), config.denom.clone())],
+            }
+            .into(),
+        );
+    }
+    if !penalty.is_zero() {
+        messages.push(
+            BankMsg::Send {
+                to_address: config.treasury_addr.to_string(),
+                amount: vec![coin(penalty.u128(), config.denom)],
+            }
+            .into(),
+        );
+    }
 
     Ok(Response::new()
+        .add_messages(messages)
         .add_attribute("protocol", "vendetta-markets")
         .add_attribute("market_type", "parimutuel")
-        .add_attribute("action", "update_market")
+        .add_attribute("action", "claim_commitments")
         .add_attribute("sender", info.sender)
-        .add_attribute("start_timestamp", start_timestamp.to_string())
-        .add_attribute("total_home", TOTAL_HOME.load(deps.storage)?.to_string())
-        .add_attribute("total_away", TOTAL_AWAY.load(deps.storage)?.to_string())
-        .add_attribute("total_draw", TOTAL_DRAW.load(deps.storage)?.to_string()))
+        .add_attribute("refund", refund)
+        .add_attribute("penalty", penalty))
 }
 
-fn execute_score(
+/// Time up until which bets are accepted, 5 minutes before the start of the match
+fn betting_cutoff(market: &Market) -> u64 {
+    market.start_timestamp.saturating_sub(5 * 60)
+}
+
+/// Time up until which sealed bets can be revealed
+fn reveal_deadline(market: &Market, sealed_bets: &SealedBets) -> u64 {
+    betting_cutoff(market) + sealed_bets.reveal_window
+}
+
+/// Rejects addresses on the local blocklist or not allowed by the compliance registry
+fn check_allowed(deps: Deps, config: &Config, addr: &Addr) -> Result<(), ContractError> {
+    if BLOCKLIST.has(deps.storage, addr.clone()) {
+        return Err(ContractError::AddressNotAllowed(addr.to_string()));
+    }
+
+    if let Some(compliance_contract) = &config.compliance_contract {
+        let response: IsAllowedResponse = deps.querier.query_wasm_smart(
+            compliance_contract,
+            &ComplianceQueryMsg::IsAllowed {
+                address: addr.to_string(),
+            },
+        )?;
+        if !response.allowed {
+            return Err(ContractError::AddressNotAllowed(addr.to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects addresses that control the market, which are the admin, the
+/// treasury, whoever resolves it and any address associated with them
+fn check_not_privileged(
+    storage: &dyn Storage,
+    config: &Config,
+    addr: &Addr,
+) -> Result<(), ContractError> {
+    let is_resolver = match &config.resolution {
+        Resolution::OPTIMISTIC {
+            arbiter,
+            ..
+        } => arbiter == addr,
+        Resolution::COMMITTEE {
+            resolvers,
+            ..
+        } => resolvers.contains(addr),
+        Resolution::ADMIN
+        | Resolution::ATTESTATION {
+            ..
+        } => false,
+    };
+
+    if *addr == config.admin_addr
+        || *addr == config.treasury_addr
+        || is_resolver
+        || ASSOCIATED.has(storage, addr.clone())
+    {
+        return Err(ContractError::ConflictOfInterest(addr.to_string()));
+    }
+
+    Ok(())
+}
+
+fn execute_claim_winnings(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    result: MarketResult,
+    receiver: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let market = MARKET.load(deps.storage)?;
 
-    if info.sender != config.admin_addr {
-        return Err(ContractError::Unauthorized {});
+    let addr = match receiver {
+        Some(receiver) => deps.api.addr_validate(receiver.as_str())?,
+        None => info.sender.clone(),
+    };
+
+    if market.is_unresolved() {
+        return Err(ContractError::MarketNotClosed {});
     }
 
-    if !market.is_drawable && result == MarketResult::DRAW {
-        return Err(ContractError::MarketNotDrawable {});
+    // Bet slips are claimed by whoever holds them instead
+    if config.slip_collection.is_some() {
+        return Err(ContractError::PositionsTokenized {});
     }
 
-    if market.status != Status::ACTIVE {
-        return Err(ContractError::MarketNotActive {});
+    if CLAIMS.has(deps.storage, addr.clone()) {
+        return Err(ContractError::ClaimAlreadyMade {});
     }
 
-    // Market can only be scored after 30 minutes of its start timestamp
-    if env.block.time.seconds() < market.start_timestamp + 30 * 60 {
-        return Err(ContractError::MarketNotScoreable {});
+    let payout = calculate_payout(deps.storage, &config, &market, &addr)?;
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+
+    if payout > 0 {
+        messages.push(
+            BankMsg::Send {
+                to_address: addr.to_string(),
+                amount: vec![coin(payout, config.denom)],
+            }
+            .into(),
+        );
+    } else {
+        return Err(ContractError::NoWinnings {});
     }
 
-    let mut market = market;
-    market.status = Status::CLOSED;
-    market.result = Some(result.clone());
-    MARKET.save(deps.storage, &market)?;
+    CLAIMS.save(deps.storage, addr.clone(), &true)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(claim_attributes(
+        &info.sender,
+        &addr,
+        payout,
+    )))
+}
 
-    let total_home = TOTAL_HOME.load(deps.storage)?;
-    let total_away = TOTAL_AWAY.load(deps.storage)?;
-    let total_draw = TOTAL_DRAW.load(deps.storage)?;
+fn claim_attributes(sender: &Addr, receiver: &Addr, payout: u128) -> Vec<Attribute> {
+    vec![
+        Attribute::new("protocol", "vendetta-markets"),
+        Attribute::new("market_type", "parimutuel"),
+        Attribute::new("action", "claim_winnings"),
+        Attribute::new("sender", sender),
+        Attribute::new("receiver", receiver),
+        Attribute::new("payout", payout.to_string()),
+    ]
+}
 
-    let winning_side = match result {
-        MarketResult::HOME => total_home,
-        MarketResult::AWAY => total_away,
-        MarketResult::DRAW => total_draw,
-    };
+/// Amount owed to an address once the market is either closed or cancelled
+fn calculate_payout(
+    storage: &dyn Storage,
+    config: &Config,
+    market: &Market,
+    addr: &Addr,
+) -> StdResult<u128> {
+    if let MarketType::LMSR {
+        ..
+    } = market.market_type
+    {
+        return calculate_lmsr_payout(storage, market, addr);
+    }
 
-    let losing_side = match result {
-        MarketResult::HOME => total_away + total_draw,
-        MarketResult::AWAY => total_home + total_draw,
-        MarketResult::DRAW => total_home + total_away,
+    if market.status == Status::CANCELLED {
+        return load_address_pool(storage, market, addr);
+    }
+
+    let result = match &market.result {
+        Some(result) => result,
+        None => return Ok(0),
     };
 
-    if winning_side == 0 || losing_side == 0 {
-        return Err(ContractError::NoWinnings {});
+    if market.market_type == MarketType::FIXED_ODDS {
+        let payout = POTENTIAL_PAYOUTS.may_load(storage, (addr.clone(), result.to_string()))?;
+        return Ok(payout.unwrap_or_default());
     }
 
-    let mut fee_amount = Uint128::zero();
-    if config.fee_bps > 0 {
-        fee_amount = Uint128::from(total_home + total_away + total_draw)
-            .multiply_ratio(Uint128::from(config.fee_bps), Uint128::from(10000_u128));
+    let winning_stake = match result.winning_outcome() {
+        Some(winning_outcome) => load_weighted_pool(storage, config, &winning_outcome, addr)?,
+        None => 0,
+    };
+
+    calculate_settled_payout(
+        storage,
+        config,
+        market,
+        result,
+        load_address_pool(storage, market, addr)?,
+        winning_stake,
+    )
+}
+
+/// Pays out each bet slip to its current holder, every slip can be claimed once
+fn execute_claim_slips(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let slip_collection = match &config.slip_collection {
+        Some(slip_collection) => slip_collection,
+        None => return Err(ContractError::SlipsNotEnabled {}),
+    };
+
+    if market.is_unresolved() {
+        return Err(ContractError::MarketNotClosed {});
     }
 
-    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+    let mut total_payout = 0_u128;
+    for token_id in token_ids.iter() {
+        let slip = match SLIPS.may_load(deps.storage, token_id.clone())? {
+            Some(slip) => slip,
+            None => return Err(ContractError::SlipNotFound {}),
+        };
+
+        if SLIP_CLAIMS.has(deps.storage, token_id.clone()) {
+            return Err(ContractError::ClaimAlreadyMade {});
+        }
+        SLIP_CLAIMS.save(deps.storage, token_id.clone(), &true)?;
+
+        let payout = calculate_slip_payout(deps.storage, &config, &market, &slip)?;
+        if payout == 0 {
+            continue;
+        }
+
+        let owner: OwnerOfResponse = deps.querier.query_wasm_smart(
+            slip_collection,
+            &Cw721QueryMsg::OwnerOf {
+                token_id: token_id.clone(),
+                include_expired: None,
+            },
+        )?;
 
-    if fee_amount > Uint128::zero() {
         messages.push(
             BankMsg::Send {
-                to_address: config.treasury_addr.to_string(),
-                amount: vec![coin(fee_amount.into(), config.denom)],
+                to_address: owner.owner,
+                amount: vec![coin(payout, config.denom.clone())],
             }
             .into(),
         );
+        total_payout += payout;
+    }
+
+    if total_payout == 0 {
+        return Err(ContractError::NoWinnings {});
     }
 
     Ok(Response::new()
         .add_messages(messages)
         .add_attribute("protocol", "vendetta-markets")
         .add_attribute("market_type", "parimutuel")
-        .add_attribute("action", "score_market")
+        .add_attribute("action", "claim_slips")
         .add_attribute("sender", info.sender)
-        .add_attribute("status", Status::CLOSED.to_string())
-        .add_attribute("result", result.to_string())
-        .add_attribute("fee_collected", fee_amount)
-        .add_attribute("total_home", total_home.to_string())
-        .add_attribute("total_away", total_away.to_string())
-        .add_attribute("total_draw", total_draw.to_string()))
+        .add_attribute("token_ids", token_ids.join(","))
+        .add_attribute("payout", total_payout.to_string()))
 }
 
-fn execute_cancel(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+fn execute_claim_referral_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let market = MARKET.load(deps.storage)?;
 
-    if info.sender != config.admin_addr {
-        return Err(ContractError::Unauthorized {});
+    if market.is_unresolved() {
+        return Err(ContractError::MarketNotClosed {});
     }
 
-    if market.status != Status::ACTIVE {
-        return Err(ContractError::MarketNotActive {});
+    if REFERRAL_CLAIMS.has(deps.storage, info.sender.clone()) {
+        return Err(ContractError::ClaimAlreadyMade {});
     }
 
-    MARKET.update(deps.storage, |mut market| -> Result<_, ContractError> {
-        market.status = Status::CANCELLED;
-        Ok(market)
-    })?;
+    let rewards = calculate_referral_rewards(deps.storage, &config, &market, &info.sender)?;
+    if rewards == 0 {
+        return Err(ContractError::NoWinnings {});
+    }
+
+    REFERRAL_CLAIMS.save(deps.storage, info.sender.clone(), &true)?;
 
     Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![coin(rewards, config.denom)],
+        })
         .add_attribute("protocol", "vendetta-markets")
         .add_attribute("market_type", "parimutuel")
-        .add_attribute("action", "cancel_market")
+        .add_attribute("action", "claim_referral_rewards")
         .add_attribute("sender", info.sender)
-        .add_attribute("status", Status::CANCELLED.to_string())
-        .add_attribute("total_home", TOTAL_HOME.load(deps.storage)?.to_string())
-        .add_attribute("total_away", TOTAL_AWAY.load(deps.storage)?.to_string())
-        .add_attribute("total_draw", TOTAL_DRAW.load(deps.storage)?.to_string()))
+        .add_attribute("rewards", rewards.to_string()))
+}
+
+/// Referral rewards owed to a referrer. Their share of the flat fees is held
+/// as the bets are placed, while the rewards set aside at scoring are split in
+/// proportion to the referred stakes the fee was charged on.
+fn calculate_referral_rewards(
+    storage: &dyn Storage,
+    config: &Config,
+    market: &Market,
+    referrer: &Addr,
+) -> StdResult<u128> {
+    if let FeeModel::FLAT_PER_BET {
+        ..
+    } = config.fee_model
+    {
+        return Ok(FLAT_FEE_REWARDS.may_load(storage, referrer.clone())?.unwrap_or_default());
+    }
+
+    let rewards = REFERRAL_REWARDS.may_load(storage)?.unwrap_or_default();
+    let total_stakes = load_referred_stakes(storage, config, market, None)?;
+    if rewards == 0 || total_stakes == 0 {
+        return Ok(0);
+    }
+
+    let stakes = load_referred_stakes(storage, config, market, Some(referrer))?;
+    Ok(Uint128::from(rewards).multiply_ratio(stakes, total_stakes).u128())
+}
+
+/// Referred stakes a fee on the scored market is charged on, of a single
+/// referrer or of every referrer. A fee on the losing pool is only charged on
+/// the losing stakes and a fee on profit only on the winning stakes.
+fn load_referred_stakes(
+    storage: &dyn Storage,
+    config: &Config,
+    market: &Market,
+    referrer: Option<&Addr>,
+) -> StdResult<u128> {
+    let volume = match referrer {
+        Some(referrer) => REFERRED_VOLUME.may_load(storage, referrer.clone())?,
+        None => TOTAL_REFERRED_VOLUME.may_load(storage)?,
+    };
+    let volume = volume.unwrap_or_default();
+
+    let winning_outcome = market.result.as_ref().and_then(MarketResult::winning_outcome);
+    let winning_stakes = match (winning_outcome, referrer) {
+        (Some(outcome), Some(referrer)) => {
+            REFERRED_STAKES.may_load(storage, (referrer.clone(), outcome.to_string()))?
+        }
+        (Some(outcome), None) => TOTAL_REFERRED_STAKES.may_load(storage, outcome.to_string())?,
+        (None, _) => None,
+    };
+    let winning_stakes = winning_stakes.unwrap_or_default();
+
+    match config.fee_model {
+        FeeModel::PROFIT_ONLY => Ok(winning_stakes),
+        FeeModel::LOSING_POOL => Ok(volume - winning_stakes),
+        _ => Ok(volume),
+    }
+}
+
+/// Amount owed to the holder of a bet slip once the market is either closed or cancelled
+fn calculate_slip_payout(
+    storage: &dyn Storage,
+    config: &Config,
+    market: &Market,
+    slip: &BetSlip,
+) -> StdResult<u128> {
+    if market.status == Status::CANCELLED {
+        return Ok(slip.stake);
+    }
+
+    let result = match &market.result {
+        Some(result) => result,
+        None => return Ok(0),
+    };
+
+    if market.market_type == MarketType::FIXED_ODDS {
+        if *result != slip.result {
+            return Ok(0);
+        }
+        return Ok((Uint128::from(slip.stake) * slip.odds).u128());
+    }
+
+    let winning_stake = match result.winning_outcome() {
+        Some(winning_outcome) if winning_outcome == slip.result => slip.stake,
+        _ => 0,
+    };
+
+    calculate_settled_payout(storage, config, market, result, slip.stake, winning_stake)
+}
+
+/// Refund and winnings of a position on a closed parimutuel market, given its
+/// stake across all outcomes and its weighted stake on the winning outcome
+fn calculate_settled_payout(
+    storage: &dyn Storage,
+    config: &Config,
+    market: &Market,
+    result: &MarketResult,
+    stake: u128,
+    winning_stake: u128,
+) -> StdResult<u128> {
+    // A push refunds every bet in full, a half result refunds half of every bet
+    let refund = match result {
+        MarketResult::PUSH => stake,
+        MarketResult::HALF_HOME | MarketResult::HALF_AWAY => {
+            Uint128::from(stake).multiply_ratio(1_u128, 2_u128).u128()
+        }
+        _ => 0,
+    };
+
+    let winnings = match result.winning_outcome() {
+        Some(winning_outcome) => {
+            let team_bets = load_winning_total(storage, config, &winning_outcome)?;
+
+            let settled_bets = calculate_settled_bets(result, load_total_pool(storage, market)?);
+            let fee_amount = calculate_market_fee(storage, config, market, result)?;
+
+            calculate_virtual_winnings(
+                settled_bets - fee_amount,
+                team_bets,
+                winning_stake,
+                calculate_settled_bets(result, market.total_virtual_weight()),
+                market.virtual_weight(&winning_outcome),
+            )
+        }
+        None => 0,
+    };
+
+    Ok(refund + winnings)
+}
+
+/// Fee taken out of the settled pool of a scored parimutuel market. Winners
+/// share what is left pro rata, so a fee on their profit or on the losing pool
+/// never takes them below their stake.
+fn calculate_market_fee(
+    storage: &dyn Storage,
+    config: &Config,
+    market: &Market,
+    result: &MarketResult,
+) -> StdResult<u128> {
+    let winning_outcome = match result.winning_outcome() {
+        Some(winning_outcome) => winning_outcome,
+        None => return Ok(0),
+    };
+
+    let settled_bets = calculate_settled_bets(result, load_total_pool(storage, market)?);
+
+    match config.fee_model {
+        FeeModel::GROSS_POOL => Ok(calculate_fee(config.fee_bps, settled_bets)),
+        FeeModel::PROFIT_ONLY => {
+            // What the winners take is capped by the virtual weights
+            let team_bets = load_winning_total(storage, config, &winning_outcome)?;
+            let winnings = calculate_virtual_winnings(
+                settled_bets,
+                team_bets,
+                team_bets,
+                calculate_settled_bets(result, market.total_virtual_weight()),
+                market.virtual_weight(&winning_outcome),
+            );
+            let profit = winnings.saturating_sub(calculate_settled_bets(result, team_bets));
+
+            Ok(calculate_fee(config.fee_bps, profit))
+        }
+        FeeModel::LOSING_POOL => {
+            let winning_bets =
+                calculate_settled_bets(result, load_total(storage, &winning_outcome)?);
+
+            Ok(calculate_fee(config.fee_bps, settled_bets.saturating_sub(winning_bets)))
+        }
+        FeeModel::FLAT_PER_BET {
+            ..
+        } => Ok(0),
+    }
+}
+
+/// Weighted stakes sharing the pool if the outcome wins, which leave out the
+/// house seed when it is redistributed to the bettors on the outcome
+fn load_winning_total(
+    storage: &dyn Storage,
+    config: &Config,
+    outcome: &MarketResult,
+) -> StdResult<u128> {
+    let total = load_weighted_total(storage, config, outcome)?;
+
+    match &config.seed {
+        Some(seed) if seed.policy == SeedPolicy::REDISTRIBUTE && total > seed.amount.u128() => {
+            Ok(total - seed.amount.u128())
+        }
+        _ => Ok(total),
+    }
+}
+
+/// Share of the house seed that goes back to the treasury once scored
+fn calculate_seed_payout(
+    storage: &dyn Storage,
+    config: &Config,
+    market: &Market,
+    result: &MarketResult,
+) -> StdResult<u128> {
+    let seed = match &config.seed {
+        Some(seed) => seed,
+        None => return Ok(0),
+    };
+
+    let winning_stake = match result.winning_outcome() {
+        Some(winning_outcome) => {
+            let total = load_weighted_total(storage, config, &winning_outcome)?;
+            if seed.policy == SeedPolicy::REDISTRIBUTE && total > seed.amount.u128() {
+                0
+            } else {
+                seed.amount.u128()
+            }
+        }
+        None => 0,
+    };
+
+    calculate_settled_payout(
+        storage,
+        config,
+        market,
+        result,
+        market.seed_stake(config),
+        winning_stake,
+    )
+}
+
+/// Returns the whole house seed to the treasury
+fn return_seed(config: &Config, market: &Market) -> Vec<CosmosMsg<ContractMsg>> {
+    let seed_stake = market.seed_stake(config);
+    if seed_stake == 0 {
+        return vec![];
+    }
+
+    vec![BankMsg::Send {
+        to_address: config.treasury_addr.to_string(),
+        amount: vec![coin(seed_stake, config.denom.clone())],
+    }
+    .into()]
+}
+
+/// Claims with the position denoms sent along, which are burned in exchange
+/// for the payout, so whoever holds the position is the one who gets paid
+#[cfg(feature = "tokenfactory")]
+fn execute_claim_positions(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receiver: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let addr = match receiver {
+        Some(receiver) => deps.api.addr_validate(receiver.as_str())?,
+        None => info.sender.clone(),
+    };
+
+    if market.is_unresolved() {
+        return Err(ContractError::MarketNotClosed {});
+    }
+
+    if info.funds.is_empty() {
+        return Err(ContractError::PaymentError {});
+    }
+
+    let outcomes = load_outcomes(deps.storage, &market)?;
+    let winning_outcome = market.result.as_ref().and_then(|result| result.winning_outcome());
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+    let mut stake = 0_u128;
+    let mut winning_stake = 0_u128;
+    for fund in info.funds.iter() {
+        let outcome = outcomes
+            .iter()
+            .find(|outcome| {
+                position_denom(env.contract.address.as_str(), &market.id, outcome) == fund.denom
+            })
+            .ok_or(ContractError::PaymentError {})?;
+
+        stake += fund.amount.u128();
+        if Some(outcome) == winning_outcome.as_ref() {
+            winning_stake += fund.amount.u128();
+        }
+
+        messages.push(
+            TokenFactoryMsg::BurnTokens {
+                denom: fund.denom.clone(),
+                amount: fund.amount,
+                burn_from_address: env.contract.address.to_string(),
+            }
+            .into(),
+        );
+    }
+
+    let payout = match (&market.status, &market.result) {
+        (Status::CANCELLED, _) => stake,
+        (_, Some(result)) => {
+            calculate_settled_payout(deps.storage, &config, &market, result, stake, winning_stake)?
+        }
+        _ => 0,
+    };
+
+    if payout == 0 {
+        return Err(ContractError::NoWinnings {});
+    }
+
+    messages.push(
+        BankMsg::Send {
+            to_address: addr.to_string(),
+            amount: vec![coin(payout, config.denom)],
+        }
+        .into(),
+    );
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "claim_winnings")
+        .add_attribute("sender", info.sender)
+        .add_attribute("receiver", addr)
+        .add_attribute("payout", payout.to_string()))
+}
+
+/// Each winning share redeems for one unit, while a cancelled market refunds
+/// what each trader paid in, pro rata if the reserve cannot cover it all
+fn calculate_lmsr_payout(storage: &dyn Storage, market: &Market, addr: &Addr) -> StdResult<u128> {
+    if market.status == Status::CANCELLED {
+        let house = HOUSE.load(storage)?;
+        let spent = NET_SPENT.may_load(storage, addr.clone())?.unwrap_or_default();
+        let reserve = house.liquidity + house.collected;
+
+        if house.refundable > reserve {
+            return Ok(Uint128::from(spent).multiply_ratio(reserve, house.refundable).u128());
+        }
+        return Ok(spent);
+    }
+
+    match &market.result {
+        Some(result) => load_pool(storage, result, addr),
+        None => Ok(0),
+    }
+}
+
+fn execute_update(
+    deps: DepsMut,
+    info: MessageInfo,
+    start_timestamp: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if info.sender != config.admin_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !market.is_unresolved() {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    // A postponed market reopens for betting, it gets locked again at the new cutoff
+    let mut market = MARKET.load(deps.storage)?;
+    market.start_timestamp = start_timestamp;
+    market.status = Status::ACTIVE;
+
+    MARKET.save(deps.storage, &market)?;
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "update_market")
+        .add_attribute("sender", info.sender)
+        .add_attribute("start_timestamp", start_timestamp.to_string())
+        .add_attributes(totals_attributes(deps.storage, &market)?))
+}
+
+fn execute_score(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    score: Option<ScoreResult>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let (result, score) = match &market.market_type {
+        // Price feed markets settle from the oracle, so anyone can score them
+        MarketType::PRICE_FEED {
+            pyth_contract,
+            price_feed_id,
+            strike,
+            max_staleness,
+            max_confidence_bps,
+        } => {
+            let id = PriceIdentifier::from_hex(price_feed_id)
+                .map_err(|_| ContractError::InvalidPriceFeed {})?;
+            let price_feed = query_price_feed(&deps.querier, pyth_contract.clone(), id)?.price_feed;
+
+            // Only a price published within max_staleness of the start timestamp
+            // settles the market, so nobody can wait for a later price that suits them
+            let price = price_feed.get_price_unchecked();
+            if price.publish_time < market.start_timestamp as i64
+                || price.publish_time > market.start_timestamp.saturating_add(*max_staleness) as i64
+            {
+                return Err(ContractError::PriceNotAvailable {});
+            }
+
+            let value =
+                price_to_decimal(price.price, price.expo).ok_or(ContractError::InvalidPrice {})?;
+
+            // Confidence interval relative to the price, in basis points
+            if Uint128::from(price.conf) * Uint128::from(10000_u128)
+                > Uint128::from(*max_confidence_bps) * Uint128::from(price.price as u128)
+            {
+                return Err(ContractError::PriceNotConfident {});
+            }
+
+            let result = if value > *strike {
+                MarketResult::HOME
+            } else {
+                MarketResult::AWAY
+            };
+            (result, value.to_string())
+        }
+        _ => {
+            if info.sender != config.admin_addr || config.resolution != Resolution::ADMIN {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let score = score.ok_or(ContractError::InvalidScore {})?;
+            (calculate_score_result(&market, &score)?, score.to_string())
+        }
+    };
+
+    score_market(deps, env, info.sender, result, score)
+}
+
+/// Maps the score reported by the admin to the result of the market
+fn calculate_score_result(
+    market: &Market,
+    score: &ScoreResult,
+) -> Result<MarketResult, ContractError> {
+    let result = match (&market.market_type, score) {
+        (
+            MarketType::WINNER
+            | MarketType::FIXED_ODDS
+            | MarketType::LMSR {
+                ..
+            },
+            ScoreResult::Result(result),
+        ) => {
+            if !market.is_drawable && *result == MarketResult::DRAW {
+                return Err(ContractError::MarketNotDrawable {});
+            }
+
+            if !market.outcomes().contains(result) {
+                return Err(ContractError::InvalidOutcome {});
+            }
+
+            result.clone()
+        }
+        (
+            MarketType::TOTALS {
+                line,
+            },
+            ScoreResult::Total {
+                total,
+            },
+        ) => calculate_totals_result(*line, *total),
+        (
+            MarketType::HANDICAP {
+                line,
+            },
+            ScoreResult::Scores {
+                home_score,
+                away_score,
+            },
+        ) => {
+            let quarters = handicap_line_in_quarters(*line).ok_or(ContractError::InvalidLine {})?;
+            calculate_handicap_result(quarters, *home_score, *away_score)
+        }
+        (
+            MarketType::CORRECT_SCORE {
+                ..
+            },
+            ScoreResult::Scores {
+                home_score,
+                away_score,
+            },
+        ) => MarketResult::SCORE {
+            home: *home_score,
+            away: *away_score,
+        },
+        _ => return Err(ContractError::InvalidScore {}),
+    };
+
+    Ok(result)
+}
+
+/// Market can only be scored after 30 minutes of its start timestamp, price
+/// feeds as soon as it is reached
+fn scoreable_timestamp(market: &Market) -> u64 {
+    match market.market_type {
+        MarketType::PRICE_FEED {
+            ..
+        } => market.start_timestamp,
+        _ => market.start_timestamp + 30 * 60,
+    }
+}
+
+/// Closes the market with its result and collects the fee of the settled bets
+fn score_market(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    result: MarketResult,
+    score: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if !market.is_unresolved() {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    if env.block.time.seconds() < scoreable_timestamp(&market) {
+        return Err(ContractError::MarketNotScoreable {});
+    }
+
+    let mut market = market;
+    market.status = Status::CLOSED;
+    market.result = Some(result.clone());
+    MARKET.save(deps.storage, &market)?;
+
+    // Sealed bets never revealed go to the winners, or to the treasury when
+    // there is no single winning outcome to share them out on
+    let mut forfeited = 0;
+    if let Some(SealedBets {
+        unrevealed: UnrevealedPolicy::FORFEIT,
+        ..
+    }) = &config.sealed_bets
+    {
+        forfeited = UNREVEALED.may_load(deps.storage)?.unwrap_or_default();
+        UNREVEALED.save(deps.storage, &0)?;
+    }
+    let mut forfeit_messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+    if forfeited > 0 {
+        match result {
+            MarketResult::PUSH | MarketResult::HALF_HOME | MarketResult::HALF_AWAY => {
+                forfeit_messages.push(
+                    BankMsg::Send {
+                        to_address: config.treasury_addr.to_string(),
+                        amount: vec![coin(forfeited, config.denom.clone())],
+                    }
+                    .into(),
+                );
+            }
+            _ => FORFEITED.save(deps.storage, &forfeited)?,
+        }
+    }
+
+    let total_bets = load_total_pool(deps.storage, &market)?;
+    let seed_stake = market.seed_stake(&config);
+
+    // A seeded market nobody bet on just returns the seed
+    if config.seed.is_some() && total_bets == seed_stake {
+        return Ok(Response::new()
+            .add_messages(forfeit_messages)
+            .add_messages(return_seed(&config, &market))
+            .add_attribute("protocol", "vendetta-markets")
+            .add_attribute("market_type", "parimutuel")
+            .add_attribute("action", "score_market")
+            .add_attribute("sender", sender)
+            .add_attribute("status", Status::CLOSED.to_string())
+            .add_attribute("result", result.to_string())
+            .add_attribute("score", score)
+            .add_attribute("fee_collected", Uint128::zero())
+            .add_attribute("seed_returned", seed_stake.to_string())
+            .add_attributes(totals_attributes(deps.storage, &market)?));
+    }
+
+    // A push refunds every bet, so there is no winning side and no fee. Fixed
+    // odds winners are paid by the house, which keeps the margin instead of a fee
+    let mut fee_amount = Uint128::zero();
+    match result.winning_outcome() {
+        // The seed is on both sides, so there is always something to settle
+        Some(_) if config.seed.is_some() => {
+            fee_amount =
+                Uint128::from(calculate_market_fee(deps.storage, &config, &market, &result)?);
+        }
+        Some(winning_outcome) if market.is_parimutuel() => {
+            let winning_side = load_total(deps.storage, &winning_outcome)?;
+            let losing_side = total_bets - winning_side;
+
+            if winning_side == 0 || losing_side == 0 {
+                return Err(ContractError::NoWinnings {});
+            }
+
+            fee_amount =
+                Uint128::from(calculate_market_fee(deps.storage, &config, &market, &result)?);
+        }
+        _ => {}
+    }
+
+    // The referrers get their share of the part of the fee charged on the
+    // stakes they referred
+    let mut referral_rewards = Uint128::zero();
+    if !fee_amount.is_zero() && config.referral_share_bps > 0 {
+        let referred_stakes = load_referred_stakes(deps.storage, &config, &market, None)?;
+        let charged_stakes = match (&config.fee_model, result.winning_outcome()) {
+            (FeeModel::PROFIT_ONLY, Some(outcome)) => {
+                let winning_stakes = load_total(deps.storage, &outcome)?;
+                match &config.seed {
+                    Some(seed) if seed.policy == SeedPolicy::REDISTRIBUTE => {
+                        winning_stakes.saturating_sub(seed.amount.u128())
+                    }
+                    _ => winning_stakes,
+                }
+            }
+            (FeeModel::LOSING_POOL, Some(outcome)) => {
+                total_bets - load_total(deps.storage, &outcome)?
+            }
+            _ => total_bets,
+        };
+        if charged_stakes > 0 {
+            referral_rewards = fee_amount
+                .multiply_ratio(referred_stakes, charged_stakes)
+                .multiply_ratio(config.referral_share_bps, 10000_u128);
+            REFERRAL_REWARDS.save(deps.storage, &referral_rewards.u128())?;
+        }
+    }
+
+    let mut messages = forfeit_messages;
+
+    let treasury_fee = fee_amount - referral_rewards;
+    if treasury_fee > Uint128::zero() {
+        messages.push(
+            BankMsg::Send {
+                to_address: config.treasury_addr.to_string(),
+                amount: vec![coin(treasury_fee.into(), config.denom.clone())],
+            }
+            .into(),
+        );
+    }
+
+    // Whatever the virtual weights hold back from the winners is not owed to anyone
+    let mut virtual_surplus = 0;
+    if let Some(winning_outcome) = result.winning_outcome() {
+        if market.is_parimutuel() && !market.virtual_weights.is_empty() {
+            let settled_bets = calculate_settled_bets(&result, total_bets) - fee_amount.u128();
+            let team_bets = load_winning_total(deps.storage, &config, &winning_outcome)?;
+            let winnings = calculate_virtual_winnings(
+                settled_bets,
+                team_bets,
+                team_bets,
+                calculate_settled_bets(&result, market.total_virtual_weight()),
+                market.virtual_weight(&winning_outcome),
+            );
+            virtual_surplus = settled_bets - winnings;
+        }
+    }
+
+    if virtual_surplus > 0 {
+        messages.push(
+            BankMsg::Send {
+                to_address: config.treasury_addr.to_string(),
+                amount: vec![coin(virtual_surplus, config.denom.clone())],
+            }
+            .into(),
+        );
+    }
+
+    let seed_payout = calculate_seed_payout(deps.storage, &config, &market, &result)?;
+    if seed_payout > 0 {
+        messages.push(
+            BankMsg::Send {
+                to_address: config.treasury_addr.to_string(),
+                amount: vec![coin(seed_payout, config.denom)],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "score_market")
+        .add_attribute("sender", sender)
+        .add_attribute("status", Status::CLOSED.to_string())
+        .add_attribute("result", result.to_string())
+        .add_attribute("score", score)
+        .add_attribute("fee_collected", fee_amount)
+        .add_attribute("referral_rewards", referral_rewards)
+        .add_attribute("seed_returned", seed_payout.to_string())
+        .add_attribute("virtual_surplus", virtual_surplus.to_string())
+        .add_attribute("forfeited", forfeited.to_string())
+        .add_attributes(totals_attributes(deps.storage, &market)?))
+}
+
+fn execute_cancel(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if info.sender != config.admin_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !market.is_unresolved() {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    let market = MARKET.update(deps.storage, |mut market| -> Result<_, ContractError> {
+        market.status = Status::CANCELLED;
+        Ok(market)
+    })?;
+
+    Ok(Response::new()
+        .add_messages(refund_bonds(deps.storage, &config)?)
+        .add_messages(return_seed(&config, &market))
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "cancel_market")
+        .add_attribute("sender", info.sender)
+        .add_attribute("status", Status::CANCELLED.to_string())
+        .add_attributes(totals_attributes(deps.storage, &market)?))
+}
+
+/// Cancels a market nobody scored by its resolution deadline, so bettors can
+/// get refunds even if the admin key or the resolvers are gone
+fn execute_force_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if !market.is_unresolved() {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    if env.block.time.seconds() < resolution_deadline(&config, &market) {
+        return Err(ContractError::DeadlineNotReached {});
+    }
+
+    force_cancel(deps, config, info.sender)
+}
+
+fn force_cancel(deps: DepsMut, config: Config, sender: Addr) -> Result<Response, ContractError> {
+    let market = MARKET.update(deps.storage, |mut market| -> Result<_, ContractError> {
+        market.status = Status::CANCELLED;
+        Ok(market)
+    })?;
+
+    Ok(Response::new()
+        .add_messages(refund_bonds(deps.storage, &config)?)
+        .add_messages(return_seed(&config, &market))
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "force_cancel_market")
+        .add_attribute("sender", sender)
+        .add_attribute("status", Status::CANCELLED.to_string())
+        .add_attributes(totals_attributes(deps.storage, &market)?))
+}
+
+/// Time after which an unscored market can be cancelled by anyone, earlier
+/// for a committee that did not reach a quorum within its voting period
+fn resolution_deadline(config: &Config, market: &Market) -> u64 {
+    let deadline = market.start_timestamp + config.resolution_deadline;
+
+    match config.resolution {
+        Resolution::COMMITTEE {
+            voting_period,
+            ..
+        } => deadline.min(scoreable_timestamp(market) + voting_period),
+        _ => deadline,
+    }
+}
+
+fn execute_deposit_liquidity(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if info.sender != config.admin_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if market.market_type != MarketType::FIXED_ODDS {
+        return Err(ContractError::MarketNotFixedOdds {});
+    }
+
+    if market.status != Status::ACTIVE {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    let amount = cw_utils::must_pay(&info, &config.denom);
+    if amount.is_err() {
+        return Err(ContractError::PaymentError {});
+    }
+    let amount = amount.unwrap();
+
+    let house = HOUSE.update(deps.storage, |mut house| -> StdResult<_> {
+        house.liquidity += amount.u128();
+        Ok(house)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "deposit_liquidity")
+        .add_attribute("sender", info.sender)
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("house_liquidity", house.liquidity.to_string()))
+}
+
+fn execute_update_odds(
+    deps: DepsMut,
+    info: MessageInfo,
+    odds: Vec<OutcomeOdds>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if info.sender != config.admin_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if market.market_type != MarketType::FIXED_ODDS {
+        return Err(ContractError::MarketNotFixedOdds {});
+    }
+
+    if market.status != Status::ACTIVE {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    let mut odds_attributes: Vec<Attribute> = vec![];
+    for outcome_odds in odds {
+        if !market.outcomes().contains(&outcome_odds.result) {
+            return Err(ContractError::InvalidOutcome {});
+        }
+
+        // Decimal odds include the stake, so anything at or below 1 is never a win
+        if outcome_odds.odds <= Decimal::one() {
+            return Err(ContractError::InvalidOdds {});
+        }
+
+        ODDS.save(deps.storage, outcome_odds.result.to_string(), &outcome_odds.odds)?;
+        odds_attributes.push(Attribute::new(
+            format!("odds_{}", outcome_odds.result.to_string().to_lowercase()),
+            outcome_odds.odds.to_string(),
+        ));
+    }
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "update_odds")
+        .add_attribute("sender", info.sender)
+        .add_attributes(odds_attributes))
+}
+
+fn execute_withdraw_liquidity(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if info.sender != config.admin_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if market.is_parimutuel() {
+        return Err(ContractError::UnsupportedMarketType {});
+    }
+
+    if market.is_unresolved() {
+        return Err(ContractError::MarketNotClosed {});
+    }
+
+    let mut house = HOUSE.load(deps.storage)?;
+    if house.withdrawn {
+        return Err(ContractError::LiquidityAlreadyWithdrawn {});
+    }
+
+    // Cancelled markets refund what the bettors paid in, so the house only gets
+    // back what is left of its liquidity. Otherwise it keeps whatever winners
+    // are not owed.
+    let amount = match (&market.market_type, &market.result) {
+        (MarketType::FIXED_ODDS, Some(result)) if market.status == Status::CLOSED => {
+            let total_stakes = load_total_pool(deps.storage, &market)?;
+            let winning_payouts =
+                TOTAL_PAYOUTS.may_load(deps.storage, result.to_string())?.unwrap_or_default();
+            house.liquidity + total_stakes - winning_payouts
+        }
+        (MarketType::FIXED_ODDS, _) => house.liquidity,
+        (_, Some(result)) if market.status == Status::CLOSED => {
+            house.liquidity + house.collected - load_total(deps.storage, result)?
+        }
+        _ => (house.liquidity + house.collected).saturating_sub(house.refundable),
+    };
+
+    house.withdrawn = true;
+    HOUSE.save(deps.storage, &house)?;
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+    if amount > 0 {
+        messages.push(
+            BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![coin(amount, config.denom)],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "withdraw_liquidity")
+        .add_attribute("sender", info.sender)
+        .add_attribute("amount", amount.to_string()))
+}
+
+fn execute_buy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    result: MarketResult,
+    shares: Uint128,
+    max_cost: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let liquidity = match market.market_type {
+        MarketType::LMSR {
+            liquidity,
+        } => liquidity,
+        _ => return Err(ContractError::UnsupportedMarketType {}),
+    };
+
+    let outcomes = market.outcomes();
+    let index = outcomes.iter().position(|outcome| *outcome == result);
+    let index = match index {
+        Some(index) => index,
+        None => return Err(ContractError::InvalidOutcome {}),
+    };
+
+    if market.status != Status::ACTIVE {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    // Shares are traded up until 5 minutes before the start of the match
+    if betting_cutoff(&market) < env.block.time.seconds() {
+        return Err(ContractError::BetsNotAccepted {});
+    }
+
+    if shares.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    check_allowed(deps.as_ref(), &config, &info.sender)?;
+    check_not_privileged(deps.storage, &config, &info.sender)?;
+
+    let payment = cw_utils::must_pay(&info, &config.denom);
+    if payment.is_err() {
+        return Err(ContractError::PaymentError {});
+    }
+    let payment = payment.unwrap();
+
+    let quantities = load_quantities(deps.storage, &outcomes)?;
+    let cost = calculate_buy_cost(&quantities, index, shares.u128(), liquidity.u128())?;
+
+    if cost > max_cost {
+        return Err(ContractError::SlippageExceeded {});
+    }
+
+    if cost > payment {
+        return Err(ContractError::PaymentError {});
+    }
+
+    add_to_pool(deps.storage, &result, &info.sender, shares.u128())?;
+    NET_SPENT.update(deps.storage, info.sender.clone(), |spent| -> StdResult<_> {
+        Ok(spent.unwrap_or_default() + cost.u128())
+    })?;
+    HOUSE.update(deps.storage, |mut house| -> StdResult<_> {
+        house.collected += cost.u128();
+        house.refundable += cost.u128();
+        Ok(house)
+    })?;
+
+    // The odds of a buy are the shares it got for each unit paid
+    let odds = Decimal::checked_from_ratio(shares, cost).unwrap_or_default();
+    let bet_id = record_bet(
+        deps.storage,
+        &info.sender,
+        &result,
+        cost.u128(),
+        env.block.time.seconds(),
+        odds,
+    )?;
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+
+    // Refund whatever was paid above the cost of the shares
+    let refund = payment - cost;
+    if !refund.is_zero() {
+        messages.push(
+            BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![coin(refund.u128(), config.denom)],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "buy_shares")
+        .add_attribute("sender", info.sender)
+        .add_attribute("result", result.to_string())
+        .add_attribute("shares", shares.to_string())
+        .add_attribute("cost", cost.to_string())
+        .add_attribute("bet_id", bet_id.to_string())
+        .add_attributes(totals_attributes(deps.storage, &market)?))
+}
+
+fn execute_sell(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    result: MarketResult,
+    shares: Uint128,
+    min_proceeds: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let liquidity = match market.market_type {
+        MarketType::LMSR {
+            liquidity,
+        } => liquidity,
+        _ => return Err(ContractError::UnsupportedMarketType {}),
+    };
+
+    let outcomes = market.outcomes();
+    let index = outcomes.iter().position(|outcome| *outcome == result);
+    let index = match index {
+        Some(index) => index,
+        None => return Err(ContractError::InvalidOutcome {}),
+    };
+
+    if market.status != Status::ACTIVE {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    // Shares are traded up until 5 minutes before the start of the match
+    if betting_cutoff(&market) < env.block.time.seconds() {
+        return Err(ContractError::BetsNotAccepted {});
+    }
+
+    if shares.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    if load_pool(deps.storage, &result, &info.sender)? < shares.u128() {
+        return Err(ContractError::InsufficientShares {});
+    }
+
+    let quantities = load_quantities(deps.storage, &outcomes)?;
+    let proceeds = calculate_sell_proceeds(&quantities, index, shares.u128(), liquidity.u128())?;
+
+    if proceeds < min_proceeds {
+        return Err(ContractError::SlippageExceeded {});
+    }
+
+    remove_from_pool(deps.storage, &result, &info.sender, shares.u128())?;
+
+    // Proceeds above what the trader paid in are profit, not refundable
+    let spent = NET_SPENT.may_load(deps.storage, info.sender.clone())?.unwrap_or_default();
+    let remaining_spent = spent.saturating_sub(proceeds.u128());
+    NET_SPENT.save(deps.storage, info.sender.clone(), &remaining_spent)?;
+    HOUSE.update(deps.storage, |mut house| -> StdResult<_> {
+        house.collected -= proceeds.u128();
+        house.refundable -= spent - remaining_spent;
+        Ok(house)
+    })?;
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+
+    if !proceeds.is_zero() {
+        messages.push(
+            BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![coin(proceeds.u128(), config.denom)],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "sell_shares")
+        .add_attribute("sender", info.sender)
+        .add_attribute("result", result.to_string())
+        .add_attribute("shares", shares.to_string())
+        .add_attribute("proceeds", proceeds.to_string())
+        .add_attributes(totals_attributes(deps.storage, &market)?))
+}
+
+fn execute_create_sell_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    result: MarketResult,
+    amount: Uint128,
+    price: Decimal,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if !market.is_parimutuel() {
+        return Err(ContractError::UnsupportedMarketType {});
+    }
+
+    if cfg!(feature = "tokenfactory") || config.slip_collection.is_some() {
+        return Err(ContractError::PositionsTokenized {});
+    }
+
+    // Positions can be traded up until the market is scored
+    if !market.is_unresolved() {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    if amount.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    if price.is_zero() {
+        return Err(ContractError::InvalidPrice {});
+    }
+
+    // Stake already listed in other orders cannot be listed again
+    let listed = LISTED
+        .may_load(deps.storage, (info.sender.clone(), result.to_string()))?
+        .unwrap_or_default();
+    if load_pool(deps.storage, &result, &info.sender)? < listed + amount.u128() {
+        return Err(ContractError::InsufficientPosition {});
+    }
+    LISTED.save(
+        deps.storage,
+        (info.sender.clone(), result.to_string()),
+        &(listed + amount.u128()),
+    )?;
+
+    let id = SELL_ORDER_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SELL_ORDER_COUNT.save(deps.storage, &id)?;
+
+    let order = SellOrder {
+        id,
+        seller: info.sender.clone(),
+        result,
+        amount: amount.u128(),
+        price,
+    };
+    SELL_ORDERS.save(deps.storage, id, &order)?;
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "create_sell_order")
+        .add_attribute("sender", info.sender)
+        .add_attribute("order_id", id.to_string())
+        .add_attribute("result", order.result.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("price", price.to_string()))
+}
+
+fn execute_fill_sell_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    order_id: u64,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if !market.is_unresolved() {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    check_allowed(deps.as_ref(), &config, &info.sender)?;
+    check_not_privileged(deps.storage, &config, &info.sender)?;
+
+    let mut order = match SELL_ORDERS.may_load(deps.storage, order_id)? {
+        Some(order) => order,
+        None => return Err(ContractError::SellOrderNotFound {}),
+    };
+
+    if amount.is_zero() || amount.u128() > order.amount {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    let payment = cw_utils::must_pay(&info, &config.denom);
+    if payment.is_err() {
+        return Err(ContractError::PaymentError {});
+    }
+    let payment = payment.unwrap();
+
+    let cost = amount.mul_ceil(order.price);
+    if payment < cost {
+        return Err(ContractError::PaymentError {});
+    }
+
+    // The stake changes hands, so the outcome totals stay the same
+    transfer_pool(deps.storage, &order.result, &order.seller, &info.sender, amount.u128())?;
+
+    LISTED.update(
+        deps.storage,
+        (order.seller.clone(), order.result.to_string()),
+        |listed| -> StdResult<_> { Ok(listed.unwrap_or_default() - amount.u128()) },
+    )?;
+
+    order.amount -= amount.u128();
+    if order.amount == 0 {
+        SELL_ORDERS.remove(deps.storage, order_id);
+    } else {
+        SELL_ORDERS.save(deps.storage, order_id, &order)?;
+    }
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![BankMsg::Send {
+        to_address: order.seller.to_string(),
+        amount: vec![coin(cost.u128(), config.denom.clone())],
+    }
+    .into()];
+
+    // Refund whatever was paid above the price of the order
+    let refund = payment - cost;
+    if !refund.is_zero() {
+        messages.push(
+            BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![coin(refund.u128(), config.denom)],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "fill_sell_order")
+        .add_attribute("sender", info.sender)
+        .add_attribute("seller", order.seller)
+        .add_attribute("order_id", order_id.to_string())
+        .add_attribute("result", order.result.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("cost", cost.to_string())
+        .add_attribute("remaining", order.amount.to_string()))
+}
+
+fn execute_cancel_sell_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    order_id: u64,
+) -> Result<Response, ContractError> {
+    let order = match SELL_ORDERS.may_load(deps.storage, order_id)? {
+        Some(order) => order,
+        None => return Err(ContractError::SellOrderNotFound {}),
+    };
+
+    if info.sender != order.seller {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    LISTED.update(
+        deps.storage,
+        (order.seller.clone(), order.result.to_string()),
+        |listed| -> StdResult<_> { Ok(listed.unwrap_or_default() - order.amount) },
+    )?;
+    SELL_ORDERS.remove(deps.storage, order_id);
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "cancel_sell_order")
+        .add_attribute("sender", info.sender)
+        .add_attribute("order_id", order_id.to_string()))
+}
+
+/// Proposes the result of an optimistically resolved market, backed by a bond
+fn execute_propose(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    score: ScoreResult,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let bond = match config.resolution {
+        Resolution::OPTIMISTIC {
+            bond,
+            ..
+        } => bond,
+        _ => return Err(ContractError::UnsupportedResolution {}),
+    };
+
+    if !market.is_unresolved() {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    if env.block.time.seconds() < scoreable_timestamp(&market) {
+        return Err(ContractError::MarketNotScoreable {});
+    }
+
+    if PROPOSAL.exists(deps.storage) {
+        return Err(ContractError::ProposalAlreadyMade {});
+    }
+
+    let bond_amount = cw_utils::must_pay(&info, &config.denom);
+    if bond_amount.is_err() || bond_amount.unwrap() != bond {
+        return Err(ContractError::PaymentError {});
+    }
+
+    let result = calculate_score_result(&market, &score)?;
+    let proposal = Proposal {
+        proposer: info.sender.clone(),
+        score,
+        result,
+        proposed_at: env.block.time.seconds(),
+        disputer: None,
+    };
+    PROPOSAL.save(deps.storage, &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "propose_result")
+        .add_attribute("sender", info.sender)
+        .add_attribute("result", proposal.result.to_string())
+        .add_attribute("score", proposal.score.to_string())
+        .add_attribute("bond", bond))
+}
+
+/// Disputes the proposed result within the challenge window, with a matching bond
+fn execute_dispute(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let (bond, challenge_window) = match config.resolution {
+        Resolution::OPTIMISTIC {
+            bond,
+            challenge_window,
+            ..
+        } => (bond, challenge_window),
+        _ => return Err(ContractError::UnsupportedResolution {}),
+    };
+
+    let mut proposal = PROPOSAL.may_load(deps.storage)?.ok_or(ContractError::NoProposal {})?;
+
+    if proposal.disputer.is_some() {
+        return Err(ContractError::AlreadyDisputed {});
+    }
+
+    if env.block.time.seconds() >= proposal.proposed_at + challenge_window {
+        return Err(ContractError::ChallengeWindowClosed {});
+    }
+
+    let bond_amount = cw_utils::must_pay(&info, &config.denom);
+    if bond_amount.is_err() || bond_amount.unwrap() != bond {
+        return Err(ContractError::PaymentError {});
+    }
+
+    proposal.disputer = Some(info.sender.clone());
+    PROPOSAL.save(deps.storage, &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "dispute_result")
+        .add_attribute("sender", info.sender)
+        .add_attribute("result", proposal.result.to_string())
+        .add_attribute("bond", bond))
+}
+
+/// Scores the market with the proposed result once the challenge window
+/// expires without a dispute, returning the bond to the proposer
+fn execute_finalize(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let (bond, challenge_window) = match config.resolution {
+        Resolution::OPTIMISTIC {
+            bond,
+            challenge_window,
+            ..
+        } => (bond, challenge_window),
+        _ => return Err(ContractError::UnsupportedResolution {}),
+    };
+
+    let proposal = PROPOSAL.may_load(deps.storage)?.ok_or(ContractError::NoProposal {})?;
+
+    if proposal.disputer.is_some() {
+        return Err(ContractError::AlreadyDisputed {});
+    }
+
+    if env.block.time.seconds() < proposal.proposed_at + challenge_window {
+        return Err(ContractError::ChallengeWindowOpen {});
+    }
+
+    PROPOSAL.remove(deps.storage);
+
+    let response =
+        score_market(deps, env, info.sender, proposal.result, proposal.score.to_string())?;
+
+    Ok(response.add_message(BankMsg::Send {
+        to_address: proposal.proposer.to_string(),
+        amount: vec![coin(bond.u128(), config.denom)],
+    }))
+}
+
+/// Decides a disputed result, the side that was right gets both bonds
+fn execute_arbitrate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    score: ScoreResult,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let (bond, arbiter) = match config.resolution {
+        Resolution::OPTIMISTIC {
+            bond,
+            arbiter,
+            ..
+        } => (bond, arbiter),
+        _ => return Err(ContractError::UnsupportedResolution {}),
+    };
+
+    if info.sender != arbiter {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let proposal = PROPOSAL.may_load(deps.storage)?.ok_or(ContractError::NoProposal {})?;
+
+    let disputer = match proposal.disputer {
+        Some(disputer) => disputer,
+        None => return Err(ContractError::NotDisputed {}),
+    };
+
+    let result = calculate_score_result(&market, &score)?;
+    let winner = if result == proposal.result {
+        proposal.proposer
+    } else {
+        disputer
+    };
+
+    PROPOSAL.remove(deps.storage);
+
+    let response = score_market(deps, env, info.sender, result, score.to_string())?;
+
+    Ok(response.add_attribute("bond_winner", winner.to_string()).add_message(BankMsg::Send {
+        to_address: winner.to_string(),
+        amount: vec![coin((bond + bond).u128(), config.denom)],
+    }))
+}
+
+/// Records the vote of a resolver, scoring the market as soon as the
+/// threshold of resolvers agree on a result
+fn execute_vote_result(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    score: ScoreResult,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let (resolvers, threshold, voting_period) = match config.resolution {
+        Resolution::COMMITTEE {
+            resolvers,
+            threshold,
+            voting_period,
+        } => (resolvers, threshold, voting_period),
+        _ => return Err(ContractError::UnsupportedResolution {}),
+    };
+
+    if !resolvers.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !market.is_unresolved() {
+        return Err(ContractError::MarketNotActive {});
+    }
+
+    if env.block.time.seconds() < scoreable_timestamp(&market) {
+        return Err(ContractError::MarketNotScoreable {});
+    }
+
+    if env.block.time.seconds() >= scoreable_timestamp(&market) + voting_period {
+        return Err(ContractError::VotingClosed {});
+    }
+
+    if VOTES.has(deps.storage, info.sender.clone()) {
+        return Err(ContractError::AlreadyVoted {});
+    }
+
+    let result = calculate_score_result(&market, &score)?;
+    VOTES.save(
+        deps.storage,
+        info.sender.clone(),
+        &Vote {
+            resolver: info.sender.clone(),
+            score: score.clone(),
+            result: result.clone(),
+        },
+    )?;
+
+    // Scores that map to the same result count towards the same result
+    let votes = VOTES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .filter(|(_, vote)| vote.result == result)
+        .count() as u32;
+
+    if votes >= threshold {
+        let response = score_market(deps, env, info.sender, result, score.to_string())?;
+        return Ok(response.add_attribute("votes", votes.to_string()));
+    }
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "vote_result")
+        .add_attribute("sender", info.sender)
+        .add_attribute("result", result.to_string())
+        .add_attribute("score", score.to_string())
+        .add_attribute("votes", votes.to_string()))
+}
+
+/// Scores the market with a result signed by one of the data providers, which
+/// anyone can submit
+fn execute_score_with_attestation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    score: ScoreResult,
+    market_id: String,
+    timestamp: u64,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    let providers = match config.resolution {
+        Resolution::ATTESTATION {
+            providers,
+        } => providers,
+        _ => return Err(ContractError::UnsupportedResolution {}),
+    };
+
+    // Attestations for other markets, or made before the match, are rejected
+    if market_id != market.id
+        || timestamp < market.start_timestamp
+        || timestamp > env.block.time.seconds()
+    {
+        return Err(ContractError::InvalidAttestation {});
+    }
+
+    let attestation = Attestation {
+        contract: env.contract.address.to_string(),
+        market_id,
+        result: score.clone(),
+        timestamp,
+    };
+    let hash = Sha256::digest(to_json_vec(&attestation)?);
+
+    let is_signed = providers.iter().any(|provider| {
+        let verification = match provider.key_type {
+            KeyType::SECP256K1 => {
+                deps.api.secp256k1_verify(&hash, &signature, &provider.public_key)
+            }
+            KeyType::ED25519 => deps.api.ed25519_verify(&hash, &signature, &provider.public_key),
+        };
+        verification.unwrap_or(false)
+    });
+    if !is_signed {
+        return Err(ContractError::InvalidAttestation {});
+    }
+
+    // Once scored the market is closed, so the same attestation cannot be replayed
+    let result = calculate_score_result(&market, &score)?;
+    score_market(deps, env, info.sender, result, score.to_string())
+}
+
+/// Returns the bonds of a pending proposal, e.g. when the market gets cancelled
+fn refund_bonds(
+    storage: &mut dyn Storage,
+    config: &Config,
+) -> StdResult<Vec<CosmosMsg<ContractMsg>>> {
+    let (bond, proposal) = match (&config.resolution, PROPOSAL.may_load(storage)?) {
+        (
+            Resolution::OPTIMISTIC {
+                bond,
+                ..
+            },
+            Some(proposal),
+        ) => (*bond, proposal),
+        _ => return Ok(vec![]),
+    };
+    PROPOSAL.remove(storage);
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+    for bonder in [Some(proposal.proposer), proposal.disputer].into_iter().flatten() {
+        messages.push(
+            BankMsg::Send {
+                to_address: bonder.to_string(),
+                amount: vec![coin(bond.u128(), config.denom.clone())],
+            }
+            .into(),
+        );
+    }
+
+    Ok(messages)
+}
+
+/// Moves stake on an outcome to another address, e.g. a team wallet or new keys
+fn execute_transfer_position(
+    deps: DepsMut,
+    info: MessageInfo,
+    outcome: MarketResult,
+    amount: Uint128,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = MARKET.load(deps.storage)?;
+
+    if config.transfers_disabled {
+        return Err(ContractError::TransfersDisabled {});
+    }
+
+    let recipient = deps.api.addr_validate(recipient.as_str())?;
+    check_allowed(deps.as_ref(), &config, &recipient)?;
+    check_not_privileged(deps.storage, &config, &recipient)?;
+
+    if let MarketType::LMSR {
+        ..
+    } = market.market_type
+    {
+        return Err(ContractError::UnsupportedMarketType {});
+    }
+
+    if cfg!(feature = "tokenfactory") || config.slip_collection.is_some() {
+        return Err(ContractError::PositionsTokenized {});
+    }
+
+    if amount.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    // Once claimed, a position is paid out and cannot move in either direction
+    if CLAIMS.has(deps.storage, info.sender.clone()) || CLAIMS.has(deps.storage, recipient.clone())
+    {
+        return Err(ContractError::ClaimAlreadyMade {});
+    }
+
+    // Stake listed in open sell orders stays with the seller
+    let listed = LISTED
+        .may_load(deps.storage, (info.sender.clone(), outcome.to_string()))?
+        .unwrap_or_default();
+    let pool = load_pool(deps.storage, &outcome, &info.sender)?;
+    if pool < listed + amount.u128() {
+        return Err(ContractError::InsufficientPosition {});
+    }
+
+    transfer_pool(deps.storage, &outcome, &info.sender, &recipient, amount.u128())?;
+
+    // Fixed odds payouts follow the stake they were locked in with
+    if market.market_type == MarketType::FIXED_ODDS {
+        let payout = POTENTIAL_PAYOUTS
+            .may_load(deps.storage, (info.sender.clone(), outcome.to_string()))?
+            .unwrap_or_default();
+        let moved_payout = Uint128::from(payout).multiply_ratio(amount, pool).u128();
+
+        POTENTIAL_PAYOUTS.save(
+            deps.storage,
+            (info.sender.clone(), outcome.to_string()),
+            &(payout - moved_payout),
+        )?;
+        POTENTIAL_PAYOUTS.update(
+            deps.storage,
+            (recipient.clone(), outcome.to_string()),
+            |payout| -> StdResult<_> { Ok(payout.unwrap_or_default() + moved_payout) },
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "transfer_position")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("outcome", outcome.to_string())
+        .add_attribute("amount", amount.to_string()))
+}
+
+fn execute_update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    fee_bps: Option<u64>,
+    treasury_addr: Option<String>,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.admin_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Sealed bets count as soon as they are committed, revealed or not
+    let has_bets = !BETTORS.is_empty(deps.storage)
+        || !COMMITMENTS.is_empty(deps.storage)
+        || UNREVEALED.may_load(deps.storage)?.unwrap_or_default() > 0;
+    let mut attributes: Vec<Attribute> = vec![];
+
+    if let Some(fee_bps) = fee_bps {
+        if fee_bps > MAX_FEE_BPS {
+            return Err(ContractError::InvalidFee(MAX_FEE_BPS));
+        }
+
+        if fee_bps <= config.fee_bps {
+            config.fee_bps = fee_bps;
+            PENDING_FEE.remove(deps.storage);
+            attributes.push(Attribute::new("fee_bps", fee_bps.to_string()));
+        } else {
+            // Bettors must never pay more than the fee they bet under
+            if has_bets {
+                return Err(ContractError::MarketHasBets {});
+            }
+
+            let effective_at = env.block.time.seconds() + FEE_TIMELOCK;
+            PENDING_FEE.save(
+                deps.storage,
+                &PendingFee {
+                    fee_bps,
+                    effective_at,
+                },
+            )?;
+            attributes.push(Attribute::new("pending_fee_bps", fee_bps.to_string()));
+            attributes.push(Attribute::new("effective_at", effective_at.to_string()));
+        }
+    }
+
+    if let Some(treasury_addr) = treasury_addr {
+        config.treasury_addr = deps.api.addr_validate(&treasury_addr)?;
+        attributes.push(Attribute::new("treasury_addr", treasury_addr));
+    }
+
+    if let Some(denom) = denom {
+        // Nothing may be held in the current denom, be it bets, seed or liquidity
+        let balance = deps.querier.query_balance(&env.contract.address, &config.denom)?;
+        if has_bets || !balance.amount.is_zero() {
+            return Err(ContractError::MarketHasBets {});
+        }
+
+        attributes.push(Attribute::new("denom", denom.clone()));
+        config.denom = denom;
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "update_config")
+        .add_attribute("sender", info.sender)
+        .add_attributes(attributes))
+}
+
+/// Applies a pending fee increase on the first bet if its timelock passed,
+/// otherwise drops it as the market now has bets. Either way the bet carries
+/// an attribute saying which.
+fn apply_pending_fee(
+    storage: &mut dyn Storage,
+    env: &Env,
+    mut config: Config,
+) -> StdResult<(Config, Vec<Attribute>)> {
+    let mut fee_attributes: Vec<Attribute> = vec![];
+    if let Some(pending_fee) = PENDING_FEE.may_load(storage)? {
+        PENDING_FEE.remove(storage);
+
+        if env.block.time.seconds() >= pending_fee.effective_at {
+            config.fee_bps = pending_fee.fee_bps;
+            CONFIG.save(storage, &config)?;
+            fee_attributes.push(Attribute::new("fee_bps", pending_fee.fee_bps.to_string()));
+        } else {
+            fee_attributes.push(Attribute::new("dropped_fee_bps", pending_fee.fee_bps.to_string()));
+        }
+    }
+
+    Ok((config, fee_attributes))
+}
+
+fn execute_update_blocklist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.admin_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes: Vec<Attribute> = vec![];
+    for address in add.iter() {
+        BLOCKLIST.save(deps.storage, deps.api.addr_validate(address)?, &())?;
+    }
+    if !add.is_empty() {
+        attributes.push(Attribute::new("added", add.join(",")));
+    }
+
+    for address in remove.iter() {
+        BLOCKLIST.remove(deps.storage, deps.api.addr_validate(address)?);
+    }
+    if !remove.is_empty() {
+        attributes.push(Attribute::new("removed", remove.join(",")));
+    }
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "update_blocklist")
+        .add_attribute("sender", info.sender)
+        .add_attributes(attributes))
+}
+
+fn execute_update_associated_addresses(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.admin_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes: Vec<Attribute> = vec![];
+    for address in add.iter() {
+        ASSOCIATED.save(deps.storage, deps.api.addr_validate(address)?, &())?;
+    }
+    if !add.is_empty() {
+        attributes.push(Attribute::new("added", add.join(",")));
+    }
+
+    for address in remove.iter() {
+        ASSOCIATED.remove(deps.storage, deps.api.addr_validate(address)?);
+    }
+    if !remove.is_empty() {
+        attributes.push(Attribute::new("removed", remove.join(",")));
+    }
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "update_associated_addresses")
+        .add_attribute("sender", info.sender)
+        .add_attributes(attributes))
+}
+
+fn execute_set_transfers_disabled(
+    deps: DepsMut,
+    info: MessageInfo,
+    disabled: bool,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.admin_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.transfers_disabled = disabled;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("protocol", "vendetta-markets")
+        .add_attribute("market_type", "parimutuel")
+        .add_attribute("action", "set_transfers_disabled")
+        .add_attribute("sender", info.sender)
+        .add_attribute("transfers_disabled", disabled.to_string()))
+}
+
+/// Creates a position denom for each fixed outcome of parimutuel markets,
+/// correct score outcomes get theirs with the first bet on them
+#[cfg(feature = "tokenfactory")]
+fn create_position_denoms(market: &Market) -> Vec<CosmosMsg<ContractMsg>> {
+    if !market.is_parimutuel() {
+        return vec![];
+    }
+
+    market
+        .outcomes()
+        .iter()
+        .map(|outcome| {
+            TokenFactoryMsg::CreateDenom {
+                subdenom: position_subdenom(&market.id, outcome),
+            }
+            .into()
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "tokenfactory"))]
+fn create_position_denoms(_market: &Market) -> Vec<CosmosMsg<ContractMsg>> {
+    vec![]
+}
+
+/// Mints the position denom of a parimutuel bet 1:1 with its stake
+#[cfg(feature = "tokenfactory")]
+fn mint_position(
+    env: &Env,
+    market: &Market,
+    result: &MarketResult,
+    amount: Uint128,
+    recipient: &Addr,
+    is_new_outcome: bool,
+) -> Vec<CosmosMsg<ContractMsg>> {
+    if !market.is_parimutuel() {
+        return vec![];
+    }
+
+    let mut messages: Vec<CosmosMsg<ContractMsg>> = vec![];
+    if is_new_outcome {
+        messages.push(
+            TokenFactoryMsg::CreateDenom {
+                subdenom: position_subdenom(&market.id, result),
+            }
+            .into(),
+        );
+    }
+    messages.push(
+        TokenFactoryMsg::MintTokens {
+            denom: position_denom(env.contract.address.as_str(), &market.id, result),
+            amount,
+            mint_to_address: recipient.to_string(),
+        }
+        .into(),
+    );
+
+    messages
+}
+
+#[cfg(not(feature = "tokenfactory"))]
+fn mint_position(
+    _env: &Env,
+    _market: &Market,
+    _result: &MarketResult,
+    _amount: Uint128,
+    _recipient: &Addr,
+    _is_new_outcome: bool,
+) -> Vec<CosmosMsg<ContractMsg>> {
+    vec![]
+}
+
+/// Outstanding shares of each outcome, in the same order as the outcomes
+fn load_quantities(storage: &dyn Storage, outcomes: &[MarketResult]) -> StdResult<Vec<u128>> {
+    outcomes.iter().map(|outcome| load_total(storage, outcome)).collect()
+}
+
+/// Totals of every outcome of the market, emitted on each market update
+fn totals_attributes(storage: &dyn Storage, market: &Market) -> StdResult<Vec<Attribute>> {
+    // Winner markets always report the draw total, even if not drawable
+    let outcomes = match market.market_type {
+        MarketType::WINNER
+        | MarketType::FIXED_ODDS
+        | MarketType::LMSR {
+            ..
+        } => {
+            vec![MarketResult::HOME, MarketResult::AWAY, MarketResult::DRAW]
+        }
+        _ => load_outcomes(storage, market)?,
+    };
+
+    let mut attributes = vec![];
+    for outcome in outcomes {
+        attributes.push(Attribute::new(
+            format!("total_{}", outcome.to_string().to_lowercase()),
+            load_total(storage, &outcome)?.to_string(),
+        ));
+    }
+    Ok(attributes)
 }
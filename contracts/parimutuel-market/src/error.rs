@@ -24,6 +24,171 @@ pub enum ContractError {
     #[error("Market not scoreable")]
     MarketNotScoreable {},
 
+    #[error("Invalid outcome for this market")]
+    InvalidOutcome {},
+
+    #[error("Invalid score for this market")]
+    InvalidScore {},
+
+    #[error("Invalid line")]
+    InvalidLine {},
+
+    #[error("Invalid liquidity")]
+    InvalidLiquidity {},
+
+    #[error("Invalid max outcomes")]
+    InvalidMaxOutcomes {},
+
+    #[error("Max outcomes reached")]
+    MaxOutcomesReached {},
+
+    #[error("Market is not a fixed odds market")]
+    MarketNotFixedOdds {},
+
+    #[error("Invalid odds")]
+    InvalidOdds {},
+
+    #[error("Odds not set for this outcome")]
+    OddsNotSet {},
+
+    #[error("Max liability exceeded")]
+    MaxLiabilityExceeded {},
+
+    #[error("Liquidity already withdrawn")]
+    LiquidityAlreadyWithdrawn {},
+
+    #[error("Unsupported market type")]
+    UnsupportedMarketType {},
+
+    #[error("Positions are tokenized")]
+    PositionsTokenized {},
+
+    #[error("Insufficient subsidy, at least {0} required")]
+    InsufficientSubsidy(u128),
+
+    #[error("Insufficient shares")]
+    InsufficientShares {},
+
+    #[error("Slippage limit exceeded")]
+    SlippageExceeded {},
+
+    #[error("Bet slips not enabled")]
+    SlipsNotEnabled {},
+
+    #[error("Bet slip not found")]
+    SlipNotFound {},
+
+    #[error("Unsupported resolution")]
+    UnsupportedResolution {},
+
+    #[error("Result already proposed")]
+    ProposalAlreadyMade {},
+
+    #[error("No result proposed")]
+    NoProposal {},
+
+    #[error("Result already disputed")]
+    AlreadyDisputed {},
+
+    #[error("Result not disputed")]
+    NotDisputed {},
+
+    #[error("Challenge window still open")]
+    ChallengeWindowOpen {},
+
+    #[error("Challenge window closed")]
+    ChallengeWindowClosed {},
+
+    #[error("Invalid resolvers")]
+    InvalidResolvers {},
+
+    #[error("Invalid attestation")]
+    InvalidAttestation {},
+
+    #[error("Vote already cast")]
+    AlreadyVoted {},
+
+    #[error("Voting closed")]
+    VotingClosed {},
+
+    #[error("Invalid resolution deadline")]
+    InvalidResolutionDeadline {},
+
+    #[error("Invalid time weight")]
+    InvalidTimeWeight {},
+
+    #[error("Invalid fee, at most {0} basis points")]
+    InvalidFee(u64),
+
+    #[error("Market already has bets")]
+    MarketHasBets {},
+
+    #[error("Address not allowed: {0}")]
+    AddressNotAllowed(String),
+
+    #[error("Conflict of interest: {0}")]
+    ConflictOfInterest(String),
+
+    #[error("Invalid reveal window")]
+    InvalidRevealWindow {},
+
+    #[error("Invalid penalty")]
+    InvalidPenalty {},
+
+    #[error("Invalid commitment")]
+    InvalidCommitment {},
+
+    #[error("Sealed bets not enabled")]
+    SealedBetsNotEnabled {},
+
+    #[error("Bets must be sealed")]
+    BetsMustBeSealed {},
+
+    #[error("Commitment already made")]
+    CommitmentAlreadyMade {},
+
+    #[error("Commitment not found")]
+    CommitmentNotFound {},
+
+    #[error("Reveal window not open")]
+    RevealWindowNotOpen {},
+
+    #[error("Reveal window still open")]
+    RevealWindowOpen {},
+
+    #[error("Invalid referral share")]
+    InvalidReferralShare {},
+
+    #[error("Invalid referrer")]
+    InvalidReferrer {},
+
+    #[error("Resolution deadline not reached")]
+    DeadlineNotReached {},
+
+    #[error("Transfers disabled")]
+    TransfersDisabled {},
+
+    #[error("Insufficient position")]
+    InsufficientPosition {},
+
+    #[error("Invalid price")]
+    InvalidPrice {},
+
+    #[error("Invalid price feed")]
+    InvalidPriceFeed {},
+
+    #[error("Price not available")]
+    PriceNotAvailable {},
+
+    #[error("Price confidence interval too wide")]
+    PriceNotConfident {},
+
+    #[error("Invalid amount")]
+    InvalidAmount {},
+
+    #[error("Sell order not found")]
+    SellOrderNotFound {},
+
     #[error("Bets no longer accepted")]
     BetsNotAccepted {},
 
@@ -1,17 +1,115 @@
-use cosmwasm_std::Uint128;
+use std::cmp::Ordering;
+
+use cosmwasm_std::{to_json_vec, Addr, Binary, Decimal, SignedDecimal, StdResult, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 pub mod contract;
 pub mod error;
+mod lmsr;
 pub mod msg;
 pub mod state;
+#[cfg(feature = "tokenfactory")]
+pub mod tokenfactory;
+
+use crate::state::{Bet, BetSlip, MarketResult, ScoreResult};
 
 #[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct TotalBets {
     pub total_home: u128,
     pub total_away: u128,
     pub total_draw: u128,
+    pub total_over: u128,
+    pub total_under: u128,
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct OutcomeOdds {
+    pub result: MarketResult,
+    pub odds: Decimal,
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct OutcomeLiability {
+    pub result: MarketResult,
+    pub odds: Option<Decimal>,
+    pub potential_payout: u128,
+    /// Amount the house loses if this outcome wins
+    pub liability: u128,
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct Liability {
+    pub house_liquidity: u128,
+    pub total_stakes: u128,
+    pub outcomes: Vec<OutcomeLiability>,
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct OutcomePrice {
+    pub result: MarketResult,
+    pub price: Decimal,
+}
+
+/// Page of the bet ledger. The scan stops after a bounded number of entries
+/// even if few of them matched, `next_start_after` is where to resume from and
+/// is only None once every entry was examined.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct BetHistoryPage {
+    pub bets: Vec<Bet>,
+    pub next_start_after: Option<u64>,
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct ReferralInfo {
+    pub referrer: Addr,
+    pub referred_volume: u128,
+    /// Estimated until the market is scored on a fee on the whole pool, and
+    /// held as the bets are placed on a flat fee. A fee on the losing pool or
+    /// on profit is only known once scored, so it is 0 until then.
+    pub pending_rewards: u128,
+    pub claimed: bool,
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct SlipInfo {
+    pub token_id: String,
+    pub slip: BetSlip,
+    pub claimed: bool,
+}
+
+/// Result attested by a data provider, who signs the sha256 hash of its JSON
+/// encoding. Binding it to the contract and market keeps it from being
+/// replayed on any other market.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct Attestation {
+    pub contract: String,
+    pub market_id: String,
+    pub result: ScoreResult,
+    pub timestamp: u64,
+}
+
+/// Bet committed to as the sha256 hash of its JSON encoding until revealed.
+/// Binding it to the bettor keeps anyone else from revealing a copy of it.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct SealedBet {
+    pub bettor: String,
+    pub result: MarketResult,
+    pub salt: String,
+}
+
+impl SealedBet {
+    pub fn commitment(&self) -> StdResult<Binary> {
+        Ok(Binary::from(Sha256::digest(to_json_vec(self)?).to_vec()))
+    }
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct ScoreBet {
+    pub home: u64,
+    pub away: u64,
+    pub total: u128,
 }
 
 fn calculate_parimutuel_winnings(total_bets: u128, total_team_bets: u128, total_bet: u128) -> u128 {
@@ -21,3 +119,226 @@ fn calculate_parimutuel_winnings(total_bets: u128, total_team_bets: u128, total_
 
     Uint128::from(total_bets).multiply_ratio(total_bet, total_team_bets).u128()
 }
+
+/// Stake weighted by how long before the betting cutoff it was placed
+fn calculate_weighted_stake(
+    stake: u128,
+    max_bonus: Decimal,
+    decay_period: u64,
+    time_to_cutoff: u64,
+) -> u128 {
+    let bonus = Uint128::from(stake)
+        .mul_floor(max_bonus)
+        .multiply_ratio(time_to_cutoff.min(decay_period), decay_period);
+
+    stake + bonus.u128()
+}
+
+/// Parimutuel winnings with virtual stake added to the pools, capped at the
+/// winnings of the real pools so the winners never take more than the pool
+fn calculate_virtual_winnings(
+    total_bets: u128,
+    total_team_bets: u128,
+    total_bet: u128,
+    virtual_bets: u128,
+    virtual_team_bets: u128,
+) -> u128 {
+    let winnings = calculate_parimutuel_winnings(total_bets, total_team_bets, total_bet);
+    let virtual_winnings = calculate_parimutuel_winnings(
+        total_bets + virtual_bets,
+        total_team_bets + virtual_team_bets,
+        total_bet,
+    );
+
+    winnings.min(virtual_winnings)
+}
+
+fn calculate_fee(fee_bps: u64, total_bets: u128) -> u128 {
+    if fee_bps == 0 {
+        return 0;
+    }
+
+    Uint128::from(total_bets).multiply_ratio(fee_bps, 10000_u128).u128()
+}
+
+/// Part of the total bets that is actually settled, the rest is refunded
+fn calculate_settled_bets(result: &MarketResult, total_bets: u128) -> u128 {
+    match result {
+        MarketResult::PUSH => 0,
+        MarketResult::HALF_HOME | MarketResult::HALF_AWAY => {
+            total_bets - Uint128::from(total_bets).multiply_ratio(1_u128, 2_u128).u128()
+        }
+        _ => total_bets,
+    }
+}
+
+/// Pyth price, a mantissa scaled by 10^expo, as a decimal
+fn price_to_decimal(price: i64, expo: i32) -> Option<Decimal> {
+    let price = u128::try_from(price).ok()?;
+
+    if expo <= 0 {
+        return Decimal::from_atomics(price, expo.unsigned_abs()).ok();
+    }
+
+    let price = price.checked_mul(10_u128.checked_pow(expo as u32)?)?;
+    Decimal::from_atomics(price, 0).ok()
+}
+
+fn calculate_totals_result(line: Decimal, total: u64) -> MarketResult {
+    match Decimal::from_ratio(total, 1_u128).cmp(&line) {
+        Ordering::Greater => MarketResult::OVER,
+        Ordering::Less => MarketResult::UNDER,
+        Ordering::Equal => MarketResult::PUSH,
+    }
+}
+
+/// Handicap line expressed in quarters, if it is a multiple of 0.25
+fn handicap_line_in_quarters(line: SignedDecimal) -> Option<i128> {
+    let one = SignedDecimal::one().atomics().i128();
+    let quarters = line.atomics().i128().checked_mul(4)?;
+
+    if quarters % one != 0 {
+        return None;
+    }
+
+    Some(quarters / one)
+}
+
+fn calculate_handicap_result(quarters: i128, home_score: u64, away_score: u64) -> MarketResult {
+    // Quarter lines split each stake in two halves, one on each adjacent half line
+    let (low_line, high_line) = if quarters % 2 != 0 {
+        (quarters - 1, quarters + 1)
+    } else {
+        (quarters, quarters)
+    };
+
+    let margin = (home_score as i128 - away_score as i128) * 4;
+    let settle = |line: i128| match (margin + line).cmp(&0) {
+        Ordering::Greater => MarketResult::HOME,
+        Ordering::Less => MarketResult::AWAY,
+        Ordering::Equal => MarketResult::PUSH,
+    };
+
+    match (settle(low_line), settle(high_line)) {
+        (low, high) if low == high => low,
+        (MarketResult::HOME, _) | (_, MarketResult::HOME) => MarketResult::HALF_HOME,
+        _ => MarketResult::HALF_AWAY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn totals_push_only_on_a_whole_line() {
+        use MarketResult::{OVER, PUSH, UNDER};
+
+        let cases = [
+            ("2.5", 2, UNDER),
+            ("2.5", 3, OVER),
+            ("3", 2, UNDER),
+            ("3", 3, PUSH),
+            ("3", 4, OVER),
+            ("0", 0, PUSH),
+            ("0.5", 0, UNDER),
+            ("0.5", 1, OVER),
+        ];
+
+        for (line, total, result) in cases {
+            let line = Decimal::from_str(line).unwrap();
+            assert_eq!(calculate_totals_result(line, total), result, "line {line} with {total}");
+        }
+    }
+
+    #[test]
+    fn handicap_lines_are_whole_quarters() {
+        let cases = [
+            ("0", Some(0)),
+            ("-0.25", Some(-1)),
+            ("0.25", Some(1)),
+            ("-0.75", Some(-3)),
+            ("-1.5", Some(-6)),
+            ("2", Some(8)),
+            ("-0.1", None),
+            ("1.3", None),
+        ];
+
+        for (line, quarters) in cases {
+            let line = SignedDecimal::from_str(line).unwrap();
+            assert_eq!(handicap_line_in_quarters(line), quarters, "line {line}");
+        }
+    }
+
+    #[test]
+    fn handicap_results_split_quarter_lines() {
+        use MarketResult::{AWAY, HALF_AWAY, HALF_HOME, HOME, PUSH};
+
+        // Line, then the result of a draw, a one goal home win and a one goal away win
+        let cases = [
+            ("-0.25", [HALF_AWAY, HOME, AWAY]),
+            ("0.25", [HALF_HOME, HOME, AWAY]),
+            ("-0.75", [AWAY, HALF_HOME, AWAY]),
+            ("0.75", [HOME, HOME, HALF_AWAY]),
+            ("-1", [AWAY, PUSH, AWAY]),
+            ("-1.5", [AWAY, AWAY, AWAY]),
+            ("1.5", [HOME, HOME, HOME]),
+        ];
+
+        for (line, results) in cases {
+            let quarters =
+                handicap_line_in_quarters(SignedDecimal::from_str(line).unwrap()).unwrap();
+            let scores = [(1, 1), (2, 1), (1, 2)];
+            for ((home_score, away_score), result) in scores.into_iter().zip(results) {
+                assert_eq!(
+                    calculate_handicap_result(quarters, home_score, away_score),
+                    result,
+                    "line {line} with {home_score}-{away_score}"
+                );
+            }
+        }
+
+        // Two goal margins clear every line above
+        let quarters = handicap_line_in_quarters(SignedDecimal::from_str("-1.5").unwrap()).unwrap();
+        assert_eq!(calculate_handicap_result(quarters, 2, 0), HOME);
+        assert_eq!(calculate_handicap_result(quarters, 0, 2), AWAY);
+    }
+
+    #[test]
+    fn bets_weigh_less_the_closer_they_are_to_the_cutoff() {
+        let max_bonus = Decimal::percent(50);
+        let decay_period = 60 * 60;
+
+        // Time to cutoff, then the weighted stake of 1000
+        let cases = [
+            (0, 1_000),
+            (1, 1_000),
+            (15 * 60, 1_125),
+            (30 * 60, 1_250),
+            (decay_period, 1_500),
+            (decay_period + 1, 1_500),
+            (7 * 24 * 60 * 60, 1_500),
+        ];
+
+        for (time_to_cutoff, weighted_stake) in cases {
+            assert_eq!(
+                calculate_weighted_stake(1_000, max_bonus, decay_period, time_to_cutoff),
+                weighted_stake,
+                "{time_to_cutoff} seconds before the cutoff"
+            );
+        }
+    }
+
+    #[test]
+    fn half_results_settle_half_of_the_pool() {
+        for total_bets in [0, 1, 2, 99, 100, 1_000_001] {
+            let settled = calculate_settled_bets(&MarketResult::HALF_HOME, total_bets);
+            let refunded = Uint128::from(total_bets).multiply_ratio(1_u128, 2_u128).u128();
+
+            assert_eq!(settled + refunded, total_bets);
+            assert_eq!(calculate_settled_bets(&MarketResult::HALF_AWAY, total_bets), settled);
+        }
+    }
+}
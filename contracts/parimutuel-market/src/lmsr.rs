@@ -0,0 +1,260 @@
+//! Logarithmic market scoring rule, in integer fixed point arithmetic.
+//!
+//! The cost of holding `q` outstanding shares is `C(q) = b * ln(sum(e^(q_i / b)))`,
+//! so buying or selling shares costs the difference of `C` before and after the
+//! trade, and the market maker never loses more than `b * ln(n)`.
+
+use cosmwasm_std::{Decimal256, StdError, StdResult, Uint128};
+
+/// ln(2) with 18 decimal places
+const LN_2: Decimal256 = Decimal256::raw(693_147_180_559_945_309);
+
+/// Exponents below e^-100 are smaller than the 18 decimal places precision
+const MAX_EXPONENT: u128 = 100;
+
+fn overflow() -> StdError {
+    StdError::generic_err("LMSR calculation overflow")
+}
+
+/// e^x for x >= 0
+fn exp(x: Decimal256) -> StdResult<Decimal256> {
+    // Range reduction, e^x = 2^k * e^r with 0 <= r < ln(2)
+    let k = (x / LN_2).floor();
+    let r = x - k * LN_2;
+
+    let mut term = Decimal256::one();
+    let mut sum = Decimal256::one();
+    for n in 1_u128..40 {
+        term = term * r / Decimal256::from_ratio(n, 1_u128);
+        if term.is_zero() {
+            break;
+        }
+        sum += term;
+    }
+
+    let two = Decimal256::from_ratio(2_u128, 1_u128);
+    let k = Uint128::try_from(k.to_uint_floor()).map_err(|_| overflow())?.u128();
+    for _ in 0..k {
+        sum = sum.checked_mul(two).map_err(|_| overflow())?;
+    }
+
+    Ok(sum)
+}
+
+/// ln(x) for x >= 1
+fn ln(x: Decimal256) -> Decimal256 {
+    let one = Decimal256::one();
+    let two = Decimal256::from_ratio(2_u128, 1_u128);
+
+    // Range reduction, ln(x) = k * ln(2) + ln(m) with 1 <= m < 2
+    let mut k = 0_u128;
+    let mut m = x;
+    while m >= two {
+        m /= two;
+        k += 1;
+    }
+
+    // ln(m) = 2 * atanh(z) with z = (m - 1) / (m + 1), which converges fast for z <= 1/3
+    let z = (m - one) / (m + one);
+    let z_squared = z * z;
+    let mut term = z;
+    let mut sum = z;
+    for n in (3_u128..80).step_by(2) {
+        term *= z_squared;
+        if term.is_zero() {
+            break;
+        }
+        sum += term / Decimal256::from_ratio(n, 1_u128);
+    }
+
+    LN_2 * Decimal256::from_ratio(k, 1_u128) + sum * two
+}
+
+/// Weights e^((q_i - max(q)) / b) of each outcome, shifted by the largest
+/// quantity so every exponent is at most zero and cannot overflow
+fn weights(quantities: &[u128], liquidity: u128) -> StdResult<Vec<Decimal256>> {
+    let max = quantities.iter().copied().max().unwrap_or_default();
+
+    quantities
+        .iter()
+        .map(|quantity| {
+            let exponent = Decimal256::from_ratio(max - quantity, liquidity);
+            if exponent > Decimal256::from_ratio(MAX_EXPONENT, 1_u128) {
+                return Ok(Decimal256::zero());
+            }
+            Ok(Decimal256::one() / exp(exponent)?)
+        })
+        .collect()
+}
+
+fn calculate_cost(quantities: &[u128], liquidity: u128) -> StdResult<Decimal256> {
+    let max = quantities.iter().copied().max().unwrap_or_default();
+    let sum = weights(quantities, liquidity)?.into_iter().sum::<Decimal256>();
+
+    Ok(Decimal256::from_ratio(max, 1_u128) + Decimal256::from_ratio(liquidity, 1_u128) * ln(sum))
+}
+
+/// Instant price of a share of each outcome, which always add up to one
+pub(crate) fn calculate_prices(quantities: &[u128], liquidity: u128) -> StdResult<Vec<Decimal256>> {
+    let weights = weights(quantities, liquidity)?;
+    let sum = weights.iter().copied().sum::<Decimal256>();
+
+    Ok(weights.into_iter().map(|weight| weight / sum).collect())
+}
+
+/// Worst case loss of the market maker, b * ln(n), rounded up
+pub(crate) fn calculate_subsidy(liquidity: u128, outcomes: usize) -> StdResult<Uint128> {
+    let subsidy = Decimal256::from_ratio(liquidity, 1_u128)
+        * ln(Decimal256::from_ratio(outcomes as u128, 1_u128));
+
+    Uint128::try_from(subsidy.to_uint_ceil()).map_err(|_| overflow())
+}
+
+/// Amount to pay for buying shares of an outcome, rounded up in favour of the market
+pub(crate) fn calculate_buy_cost(
+    quantities: &[u128],
+    index: usize,
+    shares: u128,
+    liquidity: u128,
+) -> StdResult<Uint128> {
+    let mut after = quantities.to_vec();
+    after[index] = after[index].checked_add(shares).ok_or_else(overflow)?;
+
+    // Rounding could only make the cost negative for a negligible trade, which is refused
+    let cost = calculate_cost(&after, liquidity)?
+        .checked_sub(calculate_cost(quantities, liquidity)?)
+        .map_err(|_| overflow())?;
+    Uint128::try_from(cost.to_uint_ceil()).map_err(|_| overflow())
+}
+
+/// Amount received for selling shares of an outcome, rounded down in favour of the market
+pub(crate) fn calculate_sell_proceeds(
+    quantities: &[u128],
+    index: usize,
+    shares: u128,
+    liquidity: u128,
+) -> StdResult<Uint128> {
+    let mut after = quantities.to_vec();
+    after[index] = after[index].checked_sub(shares).ok_or_else(overflow)?;
+
+    // Rounding could only make the proceeds negative for a negligible trade, which pays nothing
+    let proceeds = calculate_cost(quantities, liquidity)?
+        .checked_sub(calculate_cost(&after, liquidity)?)
+        .unwrap_or_default();
+    Uint128::try_from(proceeds.to_uint_floor()).map_err(|_| overflow())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn decimal(value: &str) -> Decimal256 {
+        Decimal256::from_str(value).unwrap()
+    }
+
+    /// Equal up to a relative error of 1e-12
+    fn assert_close(actual: Decimal256, expected: Decimal256) {
+        let tolerance = expected * decimal("0.000000000001") + decimal("0.000000000000001");
+        assert!(actual.abs_diff(expected) <= tolerance, "{actual} is not close to {expected}");
+    }
+
+    #[test]
+    fn exp_matches_known_values() {
+        assert_eq!(exp(Decimal256::zero()).unwrap(), Decimal256::one());
+        assert_close(exp(Decimal256::one()).unwrap(), decimal("2.718281828459045235"));
+        assert_close(exp(LN_2).unwrap(), decimal("2"));
+        assert_close(exp(decimal("0.5")).unwrap(), decimal("1.648721270700128146"));
+        assert_close(exp(decimal("10")).unwrap(), decimal("22026.465794806716516957"));
+        assert_close(
+            exp(decimal("100")).unwrap(),
+            decimal("26881171418161354484126255515800135873611118.773741922415191608"),
+        );
+    }
+
+    #[test]
+    fn ln_matches_known_values() {
+        assert_eq!(ln(Decimal256::one()), Decimal256::zero());
+        assert_close(ln(decimal("2")), LN_2);
+        assert_close(ln(decimal("2.718281828459045235")), Decimal256::one());
+        assert_close(ln(decimal("3")), decimal("1.098612288668109691"));
+        assert_close(ln(decimal("10")), decimal("2.302585092994045684"));
+        assert_close(ln(decimal("1000000")), decimal("13.815510557964274104"));
+    }
+
+    #[test]
+    fn ln_inverts_exp() {
+        for x in ["0.001", "0.3", "1", "4.2", "25", "99"] {
+            assert_close(ln(exp(decimal(x)).unwrap()), decimal(x));
+        }
+    }
+
+    #[test]
+    fn subsidy_is_b_ln_n_rounded_up() {
+        assert_eq!(calculate_subsidy(100, 2).unwrap(), Uint128::new(70));
+        assert_eq!(calculate_subsidy(1_000, 2).unwrap(), Uint128::new(694));
+        assert_eq!(calculate_subsidy(1_000, 3).unwrap(), Uint128::new(1_099));
+        assert_eq!(calculate_subsidy(1_000_000, 1).unwrap(), Uint128::zero());
+    }
+
+    #[test]
+    fn prices_sum_to_one() {
+        let cases: [&[u128]; 5] =
+            [&[0, 0], &[0, 0, 0], &[500, 0], &[1_000, 250, 30], &[1_000_000, 0, 7]];
+        for quantities in cases {
+            let prices = calculate_prices(quantities, 1_000).unwrap();
+            assert_close(prices.into_iter().sum(), Decimal256::one());
+        }
+
+        assert_eq!(calculate_prices(&[0, 0], 1_000).unwrap(), vec![decimal("0.5"); 2]);
+    }
+
+    #[test]
+    fn market_maker_loss_is_bounded_by_subsidy() {
+        let liquidity = 1_000;
+        let trades: [&[(usize, u128)]; 4] = [
+            &[(0, 10_000)],
+            &[(0, 1), (1, 1), (2, 1), (0, 5_000)],
+            &[(2, 700), (2, 700), (2, 700), (1, 3)],
+            &[(1, 123), (0, 456), (1, 789), (2, 1_011)],
+        ];
+
+        for trades in trades {
+            let mut quantities = vec![0_u128; 3];
+            let mut paid = 0_u128;
+            for (index, shares) in trades {
+                paid += calculate_buy_cost(&quantities, *index, *shares, liquidity).unwrap().u128();
+                quantities[*index] += shares;
+            }
+
+            // Whichever outcome wins, the payout exceeds what was paid by at most b * ln(n)
+            let max_payout = quantities.iter().copied().max().unwrap();
+            let subsidy = calculate_subsidy(liquidity, 3).unwrap().u128();
+            assert!(max_payout <= paid + subsidy, "{max_payout} > {paid} + {subsidy}");
+        }
+    }
+
+    #[test]
+    fn buying_then_selling_never_profits() {
+        let liquidity = 1_000;
+        let cases: [(&[u128], usize, u128); 6] = [
+            (&[0, 0], 0, 1),
+            (&[0, 0], 1, 1_000),
+            (&[5_000, 0], 0, 10),
+            (&[5_000, 0], 1, 10),
+            (&[0, 300, 600], 2, 2_500),
+            (&[1_000_000, 0, 0], 1, 1),
+        ];
+
+        for (quantities, index, shares) in cases {
+            let cost = calculate_buy_cost(quantities, index, shares, liquidity).unwrap();
+
+            let mut after = quantities.to_vec();
+            after[index] += shares;
+            let proceeds = calculate_sell_proceeds(&after, index, shares, liquidity).unwrap();
+
+            assert!(proceeds <= cost, "{proceeds} > {cost}");
+        }
+    }
+}
@@ -1,12 +1,19 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::MarketResult;
+use crate::{
+    state::{
+        FeeModel, HouseSeed, MarketResult, MarketType, Resolution, ScoreResult, SealedBets,
+        TimeWeight, VirtualWeight,
+    },
+    OutcomeOdds,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-    pub fee_bps: u64, // Fee in basis points
+    pub fee_bps: u64,                // Fee in basis points
+    pub fee_model: Option<FeeModel>, // Defaults to a fee on the whole pool
     pub denom: String,
     pub id: String,
     pub label: String,
@@ -14,6 +21,16 @@ pub struct InstantiateMsg {
     pub away_team: String,
     pub start_timestamp: u64,
     pub is_drawable: bool,
+    pub market_type: Option<MarketType>, // Defaults to a winner market
+    pub slip_collection: Option<String>, // Mints a CW721 bet slip for every bet
+    pub resolution: Option<Resolution>,  // Defaults to admin resolution
+    pub resolution_deadline: Option<u64>, // Seconds after the start timestamp, defaults to a week
+    pub time_weight: Option<TimeWeight>, // Early bettor bonus, disabled by default
+    pub seed: Option<HouseSeed>,         // Funded with the instantiation
+    pub virtual_weights: Option<Vec<VirtualWeight>>,
+    pub referral_share_bps: Option<u64>, // Share of the fee paid to referrers, in basis points
+    pub compliance_contract: Option<String>, // Registry queried for every bettor
+    pub sealed_bets: Option<SealedBets>, // Commit-reveal betting, disabled by default
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -22,24 +39,134 @@ pub enum ExecuteMsg {
     PlaceBet {
         result: MarketResult,
         receiver: Option<Addr>,
+        referrer: Option<Addr>, // Only recorded the first time one is given for each bettor
     },
     ClaimWinnings {
         receiver: Option<Addr>,
     },
+    ClaimSlips {
+        token_ids: Vec<String>,
+    },
+    ClaimReferralRewards {},
+    CommitBet {
+        commitment: Binary, // sha256 hash of the JSON encoded sealed bet
+    },
+    RevealBet {
+        result: MarketResult,
+        salt: String,
+    },
+    ClaimCommitments {},
+    Buy {
+        result: MarketResult,
+        shares: Uint128,
+        max_cost: Uint128,
+    },
+    Sell {
+        result: MarketResult,
+        shares: Uint128,
+        min_proceeds: Uint128,
+    },
+    CreateSellOrder {
+        result: MarketResult,
+        amount: Uint128,
+        price: Decimal, // Price per unit of stake
+    },
+    FillSellOrder {
+        order_id: u64,
+        amount: Uint128,
+    },
+    CancelSellOrder {
+        order_id: u64,
+    },
+    TransferPosition {
+        outcome: MarketResult,
+        amount: Uint128,
+        recipient: Addr,
+    },
+    ForceCancel {}, // Once the resolution deadline passes
+    // Optimistic resolution
+    Propose {
+        result: ScoreResult,
+    },
+    Dispute {},
+    Finalize {},
+    Arbitrate {
+        result: ScoreResult,
+    },
+    // Committee resolution
+    VoteResult {
+        result: ScoreResult,
+    },
+    // Attestation resolution
+    ScoreWithAttestation {
+        result: ScoreResult,
+        market_id: String,
+        timestamp: u64,
+        signature: Binary,
+    },
     // Admin
     Update {
         start_timestamp: u64,
     },
     Score {
-        result: MarketResult,
+        result: Option<ScoreResult>, // Price feed markets are scored from the oracle instead
     },
     Cancel {},
+    DepositLiquidity {},
+    UpdateOdds {
+        odds: Vec<OutcomeOdds>,
+    },
+    WithdrawLiquidity {},
+    SetTransfersDisabled {
+        disabled: bool,
+    },
+    UpdateConfig {
+        fee_bps: Option<u64>, // Increases only apply after a timelock, if nobody bet by then
+        treasury_addr: Option<String>,
+        denom: Option<String>, // Only before any bets
+    },
+    UpdateBlocklist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    UpdateAssociatedAddresses {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+}
+
+/// Query of the external compliance registry
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplianceQueryMsg {
+    IsAllowed {
+        address: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsAllowedResponse {
+    pub allowed: bool,
+}
+
+/// Invoked by a Neutron cron schedule or governance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    LockBetting {},
+    CancelOverdue {},
+    DistributePayouts {
+        limit: u32,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingFee {},
+    Proposal {},
+    Votes {},
     Market {},
     Bets {},
     BetsByAddress {
@@ -49,4 +176,45 @@ pub enum QueryMsg {
         address: Addr,
         result: MarketResult,
     },
+    ScoreBets {},
+    ScoreBetsByAddress {
+        address: Addr,
+    },
+    SlipsByOwner {
+        owner: Addr,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Liability {},
+    Odds {},
+    Referral {
+        referrer: Addr,
+    },
+    Commitments {
+        address: Addr,
+    },
+    BetHistory {
+        address: Option<Addr>,
+        result: Option<MarketResult>,
+        start_time: Option<u64>,  // Inclusive
+        end_time: Option<u64>,    // Exclusive
+        start_after: Option<u64>, // Bet id
+        limit: Option<u32>,
+    },
+    Blocklist {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    AssociatedAddresses {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    Price {},
+    SellOrder {
+        order_id: u64,
+    },
+    SellOrders {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
@@ -1,7 +1,7 @@
 use std::fmt;
 
-use cosmwasm_std::Addr;
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, Decimal, Order, SignedDecimal, StdResult, Storage, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -10,22 +10,382 @@ pub const MARKET: Item<Market> = Item::new("market");
 pub const POOL_HOME: Map<Addr, u128> = Map::new("pool_home");
 pub const POOL_AWAY: Map<Addr, u128> = Map::new("pool_away");
 pub const POOL_DRAW: Map<Addr, u128> = Map::new("pool_draw");
+pub const POOL_OVER: Map<Addr, u128> = Map::new("pool_over");
+pub const POOL_UNDER: Map<Addr, u128> = Map::new("pool_under");
 pub const TOTAL_HOME: Item<u128> = Item::new("total_home");
 pub const TOTAL_AWAY: Item<u128> = Item::new("total_away");
 pub const TOTAL_DRAW: Item<u128> = Item::new("total_draw");
+pub const TOTAL_OVER: Item<u128> = Item::new("total_over");
+pub const TOTAL_UNDER: Item<u128> = Item::new("total_under");
+/// Correct score pools, keyed by (address, home score, away score)
+pub const POOL_SCORE: Map<(Addr, u64, u64), u128> = Map::new("pool_score");
+/// Correct score totals, keyed by (home score, away score)
+pub const TOTAL_SCORE: Map<(u64, u64), u128> = Map::new("total_score");
+/// Fixed odds currently offered, keyed by outcome
+pub const ODDS: Map<String, Decimal> = Map::new("odds");
+/// Fixed odds payouts locked in by each address, keyed by (address, outcome)
+pub const POTENTIAL_PAYOUTS: Map<(Addr, String), u128> = Map::new("potential_payouts");
+/// Fixed odds payouts locked in for each outcome
+pub const TOTAL_PAYOUTS: Map<String, u128> = Map::new("total_payouts");
+pub const HOUSE: Item<House> = Item::new("house");
+/// Net amount each trader paid for their LMSR shares, refunded on cancellation
+pub const NET_SPENT: Map<Addr, u128> = Map::new("net_spent");
+pub const SELL_ORDERS: Map<u64, SellOrder> = Map::new("sell_orders");
+pub const SELL_ORDER_COUNT: Item<u64> = Item::new("sell_order_count");
+/// Stake listed for sale in open orders, keyed by (seller, outcome)
+pub const LISTED: Map<(Addr, String), u128> = Map::new("listed");
 pub const CLAIMS: Map<Addr, bool> = Map::new("claims");
+/// Every address that ever held a position, to distribute payouts in batches
+pub const BETTORS: Map<Addr, bool> = Map::new("bettors");
+pub const DISTRIBUTION_CURSOR: Item<Addr> = Item::new("distribution_cursor");
+/// Bet slips minted by this market, keyed by token id
+pub const SLIPS: Map<String, BetSlip> = Map::new("slips");
+pub const SLIP_COUNT: Item<u64> = Item::new("slip_count");
+pub const SLIP_CLAIMS: Map<String, bool> = Map::new("slip_claims");
+/// Pending result proposal of an optimistically resolved market
+pub const PROPOSAL: Item<Proposal> = Item::new("proposal");
+/// Votes of the resolver committee, keyed by resolver
+pub const VOTES: Map<Addr, Vote> = Map::new("votes");
+/// Time weighted stakes, keyed by (address, outcome)
+pub const WEIGHTED_POOLS: Map<(Addr, String), u128> = Map::new("weighted_pools");
+/// Time weighted stakes on each outcome
+pub const WEIGHTED_TOTALS: Map<String, u128> = Map::new("weighted_totals");
+/// Referrer of each bettor, keyed by bettor
+pub const REFERRERS: Map<Addr, Addr> = Map::new("referrers");
+/// Stake placed by the bettors each referrer brought in, keyed by referrer
+pub const REFERRED_VOLUME: Map<Addr, u128> = Map::new("referred_volume");
+pub const TOTAL_REFERRED_VOLUME: Item<u128> = Item::new("total_referred_volume");
+/// Referred stake on each outcome, keyed by (referrer, outcome)
+pub const REFERRED_STAKES: Map<(Addr, String), u128> = Map::new("referred_stakes");
+/// Referred stake on each outcome across every referrer
+pub const TOTAL_REFERRED_STAKES: Map<String, u128> = Map::new("total_referred_stakes");
+/// Share of the flat fees on referred bets held for each referrer, keyed by referrer
+pub const FLAT_FEE_REWARDS: Map<Addr, u128> = Map::new("flat_fee_rewards");
+/// Part of the fee set aside for the referrers once the market is scored
+pub const REFERRAL_REWARDS: Item<u128> = Item::new("referral_rewards");
+pub const REFERRAL_CLAIMS: Map<Addr, bool> = Map::new("referral_claims");
+/// Fee increase waiting for its timelock to pass
+pub const PENDING_FEE: Item<PendingFee> = Item::new("pending_fee");
+/// Addresses the admin blocked from betting
+pub const BLOCKLIST: Map<Addr, ()> = Map::new("blocklist");
+/// Addresses associated with the operator of the market, which may not take
+/// positions in it
+pub const ASSOCIATED: Map<Addr, ()> = Map::new("associated");
+/// Sealed bets waiting to be revealed, keyed by (bettor, base64 commitment)
+pub const COMMITMENTS: Map<(Addr, String), Commitment> = Map::new("commitments");
+/// Stake committed in sealed bets not revealed or refunded yet
+pub const UNREVEALED: Item<u128> = Item::new("unrevealed");
+/// Unrevealed stake added to the pool when the market was scored
+pub const FORFEITED: Item<u128> = Item::new("forfeited");
+pub const BET_COUNT: Item<u64> = Item::new("bet_count");
+
+pub struct BetIndexes<'a> {
+    pub bettor: MultiIndex<'a, Addr, Bet, u64>,
+    pub outcome: MultiIndex<'a, String, Bet, u64>,
+}
+
+impl<'a> IndexList<Bet> for BetIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Bet>> + '_> {
+        let indexes: Vec<&dyn Index<Bet>> = vec![&self.bettor, &self.outcome];
+        Box::new(indexes.into_iter())
+    }
+}
+
+/// Every bet placed on the market, keyed by id and indexed by bettor and
+/// outcome. Settlement only ever reads the pools, this is a record of how
+/// they were built up.
+pub fn bet_ledger<'a>() -> IndexedMap<'a, u64, Bet, BetIndexes<'a>> {
+    let indexes = BetIndexes {
+        bettor: MultiIndex::new(|_, bet| bet.bettor.clone(), "bet_ledger", "bet_ledger__bettor"),
+        outcome: MultiIndex::new(
+            |_, bet| bet.result.to_string(),
+            "bet_ledger",
+            "bet_ledger__outcome",
+        ),
+    };
+    IndexedMap::new("bet_ledger", indexes)
+}
 
 #[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct Config {
     pub admin_addr: Addr,
     pub treasury_addr: Addr,
     pub fee_bps: u64,
+    #[serde(default)]
+    pub fee_model: FeeModel,
     pub denom: String,
+    /// CW721 collection a bet slip is minted on for every bet, when set
+    #[serde(default)]
+    pub slip_collection: Option<Addr>,
+    #[serde(default)]
+    pub transfers_disabled: bool,
+    #[serde(default)]
+    pub resolution: Resolution,
+    /// Seconds after the start timestamp anyone can cancel an unscored market
+    #[serde(default = "default_resolution_deadline")]
+    pub resolution_deadline: u64,
+    /// Weights stakes by how early they were placed when set, so early bets
+    /// earn a larger share of the pool
+    #[serde(default)]
+    pub time_weight: Option<TimeWeight>,
+    #[serde(default)]
+    pub seed: Option<HouseSeed>,
+    /// Share of the fee on the stakes of referred bettors paid to their referrers
+    #[serde(default)]
+    pub referral_share_bps: u64,
+    /// Registry every bettor must be allowed by, when set
+    #[serde(default)]
+    pub compliance_contract: Option<Addr>,
+    #[serde(default)]
+    pub sealed_bets: Option<SealedBets>,
+}
+
+/// What the fee is charged on
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema, Default)]
+#[allow(non_camel_case_types)]
+pub enum FeeModel {
+    /// `fee_bps` of the whole settled pool, taken when scored
+    #[default]
+    GROSS_POOL,
+    /// `fee_bps` of what the winners take on top of their stakes, taken when scored
+    PROFIT_ONLY,
+    /// `fee_bps` of the settled stakes on the losing outcomes, taken when scored
+    LOSING_POOL,
+    /// Flat amount out of every bet, taken when the bet is placed and not
+    /// refunded if the market gets cancelled
+    FLAT_PER_BET {
+        amount: Uint128,
+    },
+}
+
+impl fmt::Display for FeeModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FeeModel::GROSS_POOL => write!(f, "GROSS_POOL"),
+            FeeModel::PROFIT_ONLY => write!(f, "PROFIT_ONLY"),
+            FeeModel::LOSING_POOL => write!(f, "LOSING_POOL"),
+            FeeModel::FLAT_PER_BET {
+                ..
+            } => write!(f, "FLAT_PER_BET"),
+        }
+    }
+}
+
+/// Unscored markets can be cancelled by anyone a week after their start
+pub fn default_resolution_deadline() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+/// How the result of the market gets reported
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema, Default)]
+#[allow(non_camel_case_types)]
+pub enum Resolution {
+    /// Scored by the admin
+    #[default]
+    ADMIN,
+    /// Anyone can propose a result by posting a bond, which stands unless it is
+    /// disputed with a matching bond within the challenge window. Disputed
+    /// results are decided by the arbiter.
+    OPTIMISTIC {
+        bond: Uint128,
+        challenge_window: u64, // Seconds
+        arbiter: Addr,
+    },
+    /// Scored once a threshold of the resolvers vote for the same result. If
+    /// that does not happen within the voting period the market can be
+    /// cancelled by anyone.
+    COMMITTEE {
+        resolvers: Vec<Addr>,
+        threshold: u32,
+        voting_period: u64, // Seconds after the market becomes scoreable
+    },
+    /// Scored by anyone with a result attestation signed by one of the data
+    /// providers
+    ATTESTATION {
+        providers: Vec<ProviderKey>,
+    },
+}
+
+/// Weight of a bet decaying linearly from `1 + max_bonus`, for bets placed
+/// `decay_period` seconds or more before the betting cutoff, down to 1 at the
+/// cutoff
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct TimeWeight {
+    pub max_bonus: Decimal,
+    pub decay_period: u64, // Seconds
+}
+
+/// House stake seeded on every outcome when the market is created, so its pools
+/// have odds from the start. It counts towards the odds like any other stake,
+/// with a weight of 1 on time weighted markets.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct HouseSeed {
+    pub amount: Uint128, // On each outcome
+    pub policy: SeedPolicy,
+}
+
+/// Bets are committed as a hash of their outcome up until the betting cutoff,
+/// and only join the pools once revealed within `reveal_window` seconds after it
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct SealedBets {
+    pub reveal_window: u64, // Seconds
+    pub unrevealed: UnrevealedPolicy,
+}
+
+/// What happens to the stake of a sealed bet that was never revealed
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+#[allow(non_camel_case_types)]
+pub enum UnrevealedPolicy {
+    /// Refunded once the reveal window closes, less a penalty sent to the treasury
+    REFUND {
+        penalty_bps: u64,
+    },
+    /// Added to the pool shared out among the winners
+    FORFEIT,
+}
+
+impl fmt::Display for UnrevealedPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnrevealedPolicy::REFUND {
+                ..
+            } => write!(f, "REFUND"),
+            UnrevealedPolicy::FORFEIT => write!(f, "FORFEIT"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct Commitment {
+    pub commitment: Binary,
+    pub amount: Uint128,
+    pub timestamp: u64,
+}
+
+/// Where the house share of the winnings goes once the market is scored
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+#[allow(non_camel_case_types)]
+pub enum SeedPolicy {
+    /// Shared out among the bettors on the winning outcome, the house only gets
+    /// it back if there are none
+    REDISTRIBUTE,
+    /// Sent to the treasury
+    RETURN,
+}
+
+impl fmt::Display for SeedPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SeedPolicy::REDISTRIBUTE => write!(f, "REDISTRIBUTE"),
+            SeedPolicy::RETURN => write!(f, "RETURN"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+#[allow(non_camel_case_types)]
+pub enum KeyType {
+    SECP256K1,
+    ED25519,
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct ProviderKey {
+    pub key_type: KeyType,
+    pub public_key: Binary,
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Resolution::ADMIN => write!(f, "ADMIN"),
+            Resolution::OPTIMISTIC {
+                ..
+            } => write!(f, "OPTIMISTIC"),
+            Resolution::COMMITTEE {
+                ..
+            } => write!(f, "COMMITTEE"),
+            Resolution::ATTESTATION {
+                ..
+            } => write!(f, "ATTESTATION"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct Vote {
+    pub resolver: Addr,
+    pub score: ScoreResult,
+    pub result: MarketResult,
+}
+
+/// Fee increase that applies on the first bet placed after `effective_at`,
+/// and is dropped if someone bets before then
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct PendingFee {
+    pub fee_bps: u64,
+    pub effective_at: u64,
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub proposer: Addr,
+    pub score: ScoreResult,
+    pub result: MarketResult,
+    pub proposed_at: u64,
+    pub disputer: Option<Addr>,
+}
+
+/// Metadata of a bet slip NFT, whoever holds it is paid out on claim
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct BetSlip {
+    pub market_id: String,
+    pub result: MarketResult,
+    pub stake: u128,
+    pub timestamp: u64,
+    /// Locked in odds on fixed odds markets, implied by the pools otherwise
+    pub odds: Decimal,
+}
+
+/// A bet, or a buy of LMSR shares whose stake is what was paid for them
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct Bet {
+    pub id: u64,
+    pub bettor: Addr,
+    pub result: MarketResult,
+    pub stake: u128,
+    pub timestamp: u64,
+    /// Locked in odds on fixed odds markets, shares per unit paid on LMSR
+    /// markets, implied by the pools otherwise
+    pub odds: Decimal,
+}
+
+/// Liquidity deposited by the operator to back fixed odds payouts, or the
+/// subsidy funding an LMSR market maker
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema, Default)]
+pub struct House {
+    pub liquidity: u128,
+    /// Net amount paid in by LMSR traders
+    pub collected: u128,
+    /// Sum of the net amount each LMSR trader paid in
+    pub refundable: u128,
+    pub withdrawn: bool,
+}
+
+/// Part of a position listed for sale, at a price per unit of stake
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct SellOrder {
+    pub id: u64,
+    pub seller: Addr,
+    pub result: MarketResult,
+    pub amount: u128,
+    pub price: Decimal,
 }
 
 #[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
 pub enum Status {
     ACTIVE,
+    LOCKED, // Past the betting cutoff, waiting to be scored
     CLOSED,
     CANCELLED,
 }
@@ -34,6 +394,7 @@ impl fmt::Display for Status {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Status::ACTIVE => write!(f, "ACTIVE"),
+            Status::LOCKED => write!(f, "LOCKED"),
             Status::CLOSED => write!(f, "CLOSED"),
             Status::CANCELLED => write!(f, "CANCELLED"),
         }
@@ -41,10 +402,20 @@ impl fmt::Display for Status {
 }
 
 #[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+#[allow(non_camel_case_types)]
 pub enum MarketResult {
     HOME,
     AWAY,
     DRAW,
+    OVER,
+    UNDER,
+    PUSH,
+    HALF_HOME,
+    HALF_AWAY,
+    SCORE {
+        home: u64,
+        away: u64,
+    },
 }
 
 impl fmt::Display for MarketResult {
@@ -53,6 +424,126 @@ impl fmt::Display for MarketResult {
             MarketResult::HOME => write!(f, "HOME"),
             MarketResult::AWAY => write!(f, "AWAY"),
             MarketResult::DRAW => write!(f, "DRAW"),
+            MarketResult::OVER => write!(f, "OVER"),
+            MarketResult::UNDER => write!(f, "UNDER"),
+            MarketResult::PUSH => write!(f, "PUSH"),
+            MarketResult::HALF_HOME => write!(f, "HALF_HOME"),
+            MarketResult::HALF_AWAY => write!(f, "HALF_AWAY"),
+            MarketResult::SCORE {
+                home,
+                away,
+            } => write!(f, "SCORE_{}_{}", home, away),
+        }
+    }
+}
+
+impl MarketResult {
+    /// Outcome whose bettors take the settled pool, if any
+    pub fn winning_outcome(&self) -> Option<MarketResult> {
+        match self {
+            MarketResult::PUSH => None,
+            MarketResult::HALF_HOME => Some(MarketResult::HOME),
+            MarketResult::HALF_AWAY => Some(MarketResult::AWAY),
+            result => Some(result.clone()),
+        }
+    }
+}
+
+/// The final result reported when scoring a market. Winner markets are scored
+/// with the `MarketResult` itself, while line based markets are scored with the
+/// final numbers and the contract derives the winning side.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum ScoreResult {
+    Result(MarketResult),
+    Total {
+        total: u64,
+    },
+    Scores {
+        home_score: u64,
+        away_score: u64,
+    },
+}
+
+impl fmt::Display for ScoreResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScoreResult::Result(result) => write!(f, "{}", result),
+            ScoreResult::Total {
+                total,
+            } => write!(f, "{}", total),
+            ScoreResult::Scores {
+                home_score,
+                away_score,
+            } => write!(f, "{}-{}", home_score, away_score),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema, Default)]
+#[allow(non_camel_case_types)]
+pub enum MarketType {
+    /// Winner of the match, with HOME/AWAY and optionally DRAW outcomes
+    #[default]
+    WINNER,
+    /// Over/under a decimal line on the final total (e.g. 2.5 maps), with
+    /// OVER/UNDER outcomes. An exact hit on a whole-number line is a push.
+    TOTALS {
+        line: Decimal,
+    },
+    /// Handicap applied to the home team (e.g. -1.5 maps), with HOME/AWAY
+    /// outcomes. Quarter lines settle half of each stake on the adjacent lines.
+    HANDICAP {
+        line: SignedDecimal,
+    },
+    /// Exact final score, with an outcome created for each score bet on, up
+    /// to a maximum number of outcomes
+    CORRECT_SCORE {
+        max_outcomes: u32,
+    },
+    /// Winner of the match at fixed odds published by the operator, with
+    /// payouts backed by house liquidity instead of the other bettors
+    FIXED_ODDS,
+    /// Winner of the match as tradable outcome shares, priced by a logarithmic
+    /// market scoring rule with liquidity parameter `b`. Each winning share
+    /// redeems for one unit of the denom.
+    LMSR {
+        liquidity: Uint128,
+    },
+    /// Whether a Pyth price feed is above a strike price at the start
+    /// timestamp, with HOME (above) and AWAY (at or below) outcomes. Anyone can
+    /// score it with a confident enough price published within `max_staleness`
+    /// seconds after the start timestamp. Without such a price it stays
+    /// unresolved until anyone force cancels it past the resolution deadline.
+    PRICE_FEED {
+        pyth_contract: Addr,
+        price_feed_id: String, // Hex encoded
+        strike: Decimal,
+        max_staleness: u64, // Seconds
+        max_confidence_bps: u64,
+    },
+}
+
+impl fmt::Display for MarketType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MarketType::WINNER => write!(f, "WINNER"),
+            MarketType::TOTALS {
+                ..
+            } => write!(f, "TOTALS"),
+            MarketType::HANDICAP {
+                ..
+            } => write!(f, "HANDICAP"),
+            MarketType::CORRECT_SCORE {
+                ..
+            } => write!(f, "CORRECT_SCORE"),
+            MarketType::FIXED_ODDS => write!(f, "FIXED_ODDS"),
+            MarketType::LMSR {
+                ..
+            } => write!(f, "LMSR"),
+            MarketType::PRICE_FEED {
+                ..
+            } => write!(f, "PRICE_FEED"),
         }
     }
 }
@@ -67,4 +558,341 @@ pub struct Market {
     pub status: Status,
     pub result: Option<MarketResult>,
     pub is_drawable: bool,
+    #[serde(default)]
+    pub market_type: MarketType,
+    /// Virtual stake on outcomes, shaping the odds without being paid out
+    #[serde(default)]
+    pub virtual_weights: Vec<VirtualWeight>,
+}
+
+/// Stake that counts towards the odds of an outcome but is never paid out.
+/// Winnings are capped at what the real pools pay, so it can only ever lower
+/// the payout of a winning outcome.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct VirtualWeight {
+    pub result: MarketResult,
+    pub amount: Uint128,
+}
+
+impl Market {
+    /// Fixed outcomes users can bet on for this market, correct score
+    /// outcomes are created on the fly and live in storage instead
+    pub fn outcomes(&self) -> Vec<MarketResult> {
+        match self.market_type {
+            MarketType::WINNER
+            | MarketType::FIXED_ODDS
+            | MarketType::LMSR {
+                ..
+            } => {
+                if self.is_drawable {
+                    vec![MarketResult::HOME, MarketResult::AWAY, MarketResult::DRAW]
+                } else {
+                    vec![MarketResult::HOME, MarketResult::AWAY]
+                }
+            }
+            MarketType::TOTALS {
+                ..
+            } => vec![MarketResult::OVER, MarketResult::UNDER],
+            MarketType::HANDICAP {
+                ..
+            }
+            | MarketType::PRICE_FEED {
+                ..
+            } => vec![MarketResult::HOME, MarketResult::AWAY],
+            MarketType::CORRECT_SCORE {
+                ..
+            } => vec![],
+        }
+    }
+
+    /// Whether winners are paid out of the pool of the losing bets
+    pub fn is_parimutuel(&self) -> bool {
+        !matches!(self.market_type, MarketType::FIXED_ODDS | MarketType::LMSR { .. })
+    }
+
+    /// Virtual stake on an outcome
+    pub fn virtual_weight(&self, result: &MarketResult) -> u128 {
+        self.virtual_weights
+            .iter()
+            .filter(|weight| weight.result == *result)
+            .map(|weight| weight.amount.u128())
+            .sum()
+    }
+
+    /// Virtual stake across the outcomes of the market
+    pub fn total_virtual_weight(&self) -> u128 {
+        self.virtual_weights.iter().map(|weight| weight.amount.u128()).sum()
+    }
+
+    /// Total house seed across the outcomes of the market
+    pub fn seed_stake(&self, config: &Config) -> u128 {
+        match &config.seed {
+            Some(seed) => seed.amount.u128() * self.outcomes().len() as u128,
+            None => 0,
+        }
+    }
+
+    /// Whether the market is yet to be either scored or cancelled
+    pub fn is_unresolved(&self) -> bool {
+        matches!(self.status, Status::ACTIVE | Status::LOCKED)
+    }
+}
+
+pub fn load_pool(storage: &dyn Storage, result: &MarketResult, addr: &Addr) -> StdResult<u128> {
+    let pool = match result {
+        MarketResult::HOME => POOL_HOME.may_load(storage, addr.clone())?,
+        MarketResult::AWAY => POOL_AWAY.may_load(storage, addr.clone())?,
+        MarketResult::DRAW => POOL_DRAW.may_load(storage, addr.clone())?,
+        MarketResult::OVER => POOL_OVER.may_load(storage, addr.clone())?,
+        MarketResult::UNDER => POOL_UNDER.may_load(storage, addr.clone())?,
+        MarketResult::SCORE {
+            home,
+            away,
+        } => POOL_SCORE.may_load(storage, (addr.clone(), *home, *away))?,
+        MarketResult::PUSH | MarketResult::HALF_HOME | MarketResult::HALF_AWAY => None,
+    };
+    Ok(pool.unwrap_or_default())
+}
+
+pub fn load_total(storage: &dyn Storage, result: &MarketResult) -> StdResult<u128> {
+    let total = match result {
+        MarketResult::HOME => TOTAL_HOME.may_load(storage)?,
+        MarketResult::AWAY => TOTAL_AWAY.may_load(storage)?,
+        MarketResult::DRAW => TOTAL_DRAW.may_load(storage)?,
+        MarketResult::OVER => TOTAL_OVER.may_load(storage)?,
+        MarketResult::UNDER => TOTAL_UNDER.may_load(storage)?,
+        MarketResult::SCORE {
+            home,
+            away,
+        } => TOTAL_SCORE.may_load(storage, (*home, *away))?,
+        MarketResult::PUSH | MarketResult::HALF_HOME | MarketResult::HALF_AWAY => None,
+    };
+    Ok(total.unwrap_or_default())
+}
+
+/// Adds the amount to both the address pool and the outcome total
+pub fn add_to_pool(
+    storage: &mut dyn Storage,
+    result: &MarketResult,
+    addr: &Addr,
+    amount: u128,
+) -> StdResult<()> {
+    let pool = load_pool(storage, result, addr)?;
+    let total = load_total(storage, result)?;
+
+    BETTORS.save(storage, addr.clone(), &true)?;
+    save_pool(storage, result, addr, pool + amount, total + amount)
+}
+
+/// Stake of an address on an outcome as it counts towards its share of the
+/// pool, time weighted if the market weights stakes
+pub fn load_weighted_pool(
+    storage: &dyn Storage,
+    config: &Config,
+    result: &MarketResult,
+    addr: &Addr,
+) -> StdResult<u128> {
+    if config.time_weight.is_none() {
+        return load_pool(storage, result, addr);
+    }
+
+    Ok(WEIGHTED_POOLS.may_load(storage, (addr.clone(), result.to_string()))?.unwrap_or_default())
+}
+
+/// Stakes on an outcome as they count towards the pool, time weighted if the
+/// market weights stakes
+pub fn load_weighted_total(
+    storage: &dyn Storage,
+    config: &Config,
+    result: &MarketResult,
+) -> StdResult<u128> {
+    if config.time_weight.is_none() {
+        return load_total(storage, result);
+    }
+
+    Ok(WEIGHTED_TOTALS.may_load(storage, result.to_string())?.unwrap_or_default())
+}
+
+/// Adds the weighted stake to both the address and the outcome weighted totals
+pub fn add_to_weighted_pool(
+    storage: &mut dyn Storage,
+    result: &MarketResult,
+    addr: &Addr,
+    weighted_stake: u128,
+) -> StdResult<()> {
+    WEIGHTED_POOLS.update(storage, (addr.clone(), result.to_string()), |pool| -> StdResult<_> {
+        Ok(pool.unwrap_or_default() + weighted_stake)
+    })?;
+    WEIGHTED_TOTALS.update(storage, result.to_string(), |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default() + weighted_stake)
+    })?;
+
+    Ok(())
+}
+
+/// Adds the house seed to the total of every outcome, it has no address pool
+pub fn seed_totals(
+    storage: &mut dyn Storage,
+    config: &Config,
+    market: &Market,
+    amount: u128,
+) -> StdResult<()> {
+    for outcome in market.outcomes() {
+        let total_item = match outcome {
+            MarketResult::HOME => TOTAL_HOME,
+            MarketResult::AWAY => TOTAL_AWAY,
+            MarketResult::DRAW => TOTAL_DRAW,
+            MarketResult::OVER => TOTAL_OVER,
+            MarketResult::UNDER => TOTAL_UNDER,
+            _ => continue,
+        };
+        total_item.update(storage, |total| -> StdResult<_> { Ok(total + amount) })?;
+
+        if config.time_weight.is_some() {
+            WEIGHTED_TOTALS.update(storage, outcome.to_string(), |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default() + amount)
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves stake between addresses along with the matching part of its weighted
+/// stake, so the outcome totals stay the same
+pub fn transfer_pool(
+    storage: &mut dyn Storage,
+    result: &MarketResult,
+    from: &Addr,
+    to: &Addr,
+    amount: u128,
+) -> StdResult<()> {
+    let pool = load_pool(storage, result, from)?;
+    remove_from_pool(storage, result, from, amount)?;
+    add_to_pool(storage, result, to, amount)?;
+
+    let weighted =
+        WEIGHTED_POOLS.may_load(storage, (from.clone(), result.to_string()))?.unwrap_or_default();
+    if weighted == 0 {
+        return Ok(());
+    }
+
+    let moved = Uint128::from(weighted).multiply_ratio(amount, pool).u128();
+    WEIGHTED_POOLS.save(storage, (from.clone(), result.to_string()), &(weighted - moved))?;
+    WEIGHTED_POOLS.update(storage, (to.clone(), result.to_string()), |pool| -> StdResult<_> {
+        Ok(pool.unwrap_or_default() + moved)
+    })?;
+
+    Ok(())
+}
+
+/// Removes the amount from both the address pool and the outcome total
+pub fn remove_from_pool(
+    storage: &mut dyn Storage,
+    result: &MarketResult,
+    addr: &Addr,
+    amount: u128,
+) -> StdResult<()> {
+    let pool = Uint128::from(load_pool(storage, result, addr)?).checked_sub(amount.into())?;
+    let total = Uint128::from(load_total(storage, result)?).checked_sub(amount.into())?;
+
+    save_pool(storage, result, addr, pool.u128(), total.u128())
+}
+
+fn save_pool(
+    storage: &mut dyn Storage,
+    result: &MarketResult,
+    addr: &Addr,
+    pool: u128,
+    total: u128,
+) -> StdResult<()> {
+    let (pool_map, total_item) = match result {
+        MarketResult::HOME => (POOL_HOME, TOTAL_HOME),
+        MarketResult::AWAY => (POOL_AWAY, TOTAL_AWAY),
+        MarketResult::DRAW => (POOL_DRAW, TOTAL_DRAW),
+        MarketResult::OVER => (POOL_OVER, TOTAL_OVER),
+        MarketResult::UNDER => (POOL_UNDER, TOTAL_UNDER),
+        MarketResult::SCORE {
+            home,
+            away,
+        } => {
+            POOL_SCORE.save(storage, (addr.clone(), *home, *away), &pool)?;
+            TOTAL_SCORE.save(storage, (*home, *away), &total)?;
+            return Ok(());
+        }
+        MarketResult::PUSH | MarketResult::HALF_HOME | MarketResult::HALF_AWAY => return Ok(()),
+    };
+
+    pool_map.save(storage, addr.clone(), &pool)?;
+    total_item.save(storage, &total)?;
+
+    Ok(())
+}
+
+/// Every outcome of the market, including the correct score outcomes created so far
+pub fn load_outcomes(storage: &dyn Storage, market: &Market) -> StdResult<Vec<MarketResult>> {
+    let mut outcomes = market.outcomes();
+    if let MarketType::CORRECT_SCORE {
+        ..
+    } = market.market_type
+    {
+        for score in TOTAL_SCORE.keys(storage, None, None, Order::Ascending) {
+            let (home, away) = score?;
+            outcomes.push(MarketResult::SCORE {
+                home,
+                away,
+            });
+        }
+    }
+    Ok(outcomes)
+}
+
+/// Appends a bet to the ledger, returning its id
+pub fn record_bet(
+    storage: &mut dyn Storage,
+    bettor: &Addr,
+    result: &MarketResult,
+    stake: u128,
+    timestamp: u64,
+    odds: Decimal,
+) -> StdResult<u64> {
+    let id = BET_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    BET_COUNT.save(storage, &id)?;
+
+    bet_ledger().save(
+        storage,
+        id,
+        &Bet {
+            id,
+            bettor: bettor.clone(),
+            result: result.clone(),
+            stake,
+            timestamp,
+            odds,
+        },
+    )?;
+    Ok(id)
+}
+
+/// Sum of the bets placed on every outcome of the market, and of the sealed
+/// bets forfeited to it
+pub fn load_total_pool(storage: &dyn Storage, market: &Market) -> StdResult<u128> {
+    let mut total_pool = FORFEITED.may_load(storage)?.unwrap_or_default();
+    for outcome in load_outcomes(storage, market)? {
+        total_pool += load_total(storage, &outcome)?;
+    }
+    Ok(total_pool)
+}
+
+/// Sum of the bets an address placed on every outcome of the market
+pub fn load_address_pool(storage: &dyn Storage, market: &Market, addr: &Addr) -> StdResult<u128> {
+    let mut address_pool = 0;
+    for outcome in market.outcomes() {
+        address_pool += load_pool(storage, &outcome, addr)?;
+    }
+    for pool in POOL_SCORE.sub_prefix(addr.clone()).range(storage, None, None, Order::Ascending) {
+        let (_, amount) = pool?;
+        address_pool += amount;
+    }
+    Ok(address_pool)
 }
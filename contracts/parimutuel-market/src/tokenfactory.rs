@@ -0,0 +1,45 @@
+//! Neutron tokenfactory bindings used to represent positions as bank denoms.
+//!
+//! Each outcome of a parimutuel market gets its own denom, e.g.
+//! `factory/<contract>/<market_id>/home`, minted 1:1 with the stake of a bet and
+//! burned when it is sent back to the contract to claim.
+
+use cosmwasm_std::{CosmosMsg, CustomMsg, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::state::MarketResult;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenFactoryMsg {
+    CreateDenom {
+        subdenom: String,
+    },
+    MintTokens {
+        denom: String,
+        amount: Uint128,
+        mint_to_address: String,
+    },
+    BurnTokens {
+        denom: String,
+        amount: Uint128,
+        burn_from_address: String,
+    },
+}
+
+impl CustomMsg for TokenFactoryMsg {}
+
+impl From<TokenFactoryMsg> for CosmosMsg<TokenFactoryMsg> {
+    fn from(msg: TokenFactoryMsg) -> Self {
+        CosmosMsg::Custom(msg)
+    }
+}
+
+pub fn position_subdenom(market_id: &str, result: &MarketResult) -> String {
+    format!("{}/{}", market_id, result.to_string().to_lowercase())
+}
+
+pub fn position_denom(contract: &str, market_id: &str, result: &MarketResult) -> String {
+    format!("factory/{}/{}", contract, position_subdenom(market_id, result))
+}
@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 
 use cosmwasm_std::{
-    from_json,
+    coins, from_json,
     testing::{
         mock_dependencies_with_balance,
         mock_env,
@@ -10,9 +10,123 @@ use cosmwasm_std::{
         MockQuerier,
         MockStorage,
     },
-    Deps, OwnedDeps,
+    Addr, Coin, Deps, OwnedDeps,
 };
-use vendetta_markets_parimutuel_market::{contract::query, msg::QueryMsg};
+#[cfg(not(feature = "tokenfactory"))]
+use cw_multi_test::AppBuilder;
+use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+use vendetta_markets_parimutuel_market::{
+    contract::{execute, instantiate, query, sudo},
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{Market, MarketResult, ScoreResult},
+};
+#[cfg(feature = "tokenfactory")]
+use {
+    anyhow::{bail, Result as AnyResult},
+    cosmwasm_std::{coin, Api, BankMsg, Binary, BlockInfo, CustomQuery, Empty, Querier, Storage},
+    cw_multi_test::{BankKeeper, BankSudo, BasicAppBuilder, CosmosRouter, Module, WasmKeeper},
+    schemars::JsonSchema,
+    serde::de::DeserializeOwned,
+    vendetta_markets_parimutuel_market::tokenfactory::TokenFactoryMsg,
+};
+
+pub const ADMIN: &str = "neutron15yhlj25av4fkw6s8qwnzerp490pkxmn9094g7r";
+pub const TREASURY: &str = "neutron12v9pqx602k3rzm5hf4jewepl8na4x89ja4td24";
+pub const DENOM: &str = "untrn";
+
+/// App the market runs on, the tokenized build needs the tokenfactory
+/// messages it sends to be handled
+#[cfg(not(feature = "tokenfactory"))]
+pub type MarketApp = App;
+#[cfg(feature = "tokenfactory")]
+pub type MarketApp =
+    App<BankKeeper, MockApi, MockStorage, TokenFactory, WasmKeeper<TokenFactoryMsg, Empty>>;
+
+/// Stand-in for the tokenfactory module, denoms are minted and burned
+/// through the bank module and creating them is a no-op
+#[cfg(feature = "tokenfactory")]
+pub struct TokenFactory;
+
+#[cfg(feature = "tokenfactory")]
+impl Module for TokenFactory {
+    type ExecT = TokenFactoryMsg;
+    type QueryT = Empty;
+    type SudoT = Empty;
+
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: TokenFactoryMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: std::fmt::Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        match msg {
+            TokenFactoryMsg::CreateDenom {
+                ..
+            } => Ok(AppResponse::default()),
+            TokenFactoryMsg::MintTokens {
+                denom,
+                amount,
+                mint_to_address,
+            } => router.sudo(
+                api,
+                storage,
+                block,
+                BankSudo::Mint {
+                    to_address: mint_to_address,
+                    amount: vec![coin(amount.u128(), denom)],
+                }
+                .into(),
+            ),
+            TokenFactoryMsg::BurnTokens {
+                denom,
+                amount,
+                ..
+            } => router.execute(
+                api,
+                storage,
+                block,
+                sender,
+                BankMsg::Burn {
+                    amount: vec![coin(amount.u128(), denom)],
+                }
+                .into(),
+            ),
+        }
+    }
+
+    fn sudo<ExecC, QueryC>(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        _msg: Empty,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: std::fmt::Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        bail!("unsupported")
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        _storage: &dyn Storage,
+        _querier: &dyn Querier,
+        _block: &BlockInfo,
+        _request: Empty,
+    ) -> AnyResult<Binary> {
+        bail!("unsupported")
+    }
+}
 
 pub fn th_setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
     // let mut deps = mock_dependencies_with_balance(&[]);
@@ -35,3 +149,129 @@ pub fn th_setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
 pub fn th_query<T: serde::de::DeserializeOwned>(deps: Deps, msg: QueryMsg) -> T {
     from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
 }
+
+/// Winner market with every optional feature disabled, tests override the
+/// fields they need with struct update syntax
+pub fn th_instantiate_msg(start_timestamp: u64) -> InstantiateMsg {
+    InstantiateMsg {
+        fee_bps: 0,
+        fee_model: None,
+        denom: DENOM.to_string(),
+        id: "game-1".to_string(),
+        label: "Game 1".to_string(),
+        home_team: "Home".to_string(),
+        away_team: "Away".to_string(),
+        start_timestamp,
+        is_drawable: false,
+        market_type: None,
+        slip_collection: None,
+        resolution: None,
+        resolution_deadline: None,
+        time_weight: None,
+        seed: None,
+        virtual_weights: None,
+        referral_share_bps: None,
+        compliance_contract: None,
+        sealed_bets: None,
+    }
+}
+
+pub fn th_place_bet(
+    app: &mut MarketApp,
+    market: &Addr,
+    user: &str,
+    result: MarketResult,
+    amount: u128,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        Addr::unchecked(user),
+        market.clone(),
+        &ExecuteMsg::PlaceBet {
+            result,
+            receiver: None,
+            referrer: None,
+        },
+        &coins(amount, DENOM),
+    )
+}
+
+/// Scores the market as the admin
+pub fn th_score(
+    app: &mut MarketApp,
+    market: &Addr,
+    result: MarketResult,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::Score {
+            result: Some(ScoreResult::Result(result)),
+        },
+        &[],
+    )
+}
+
+pub fn th_claim_winnings(
+    app: &mut MarketApp,
+    market: &Addr,
+    user: &str,
+) -> anyhow::Result<AppResponse> {
+    let positions = th_positions(app, market, user);
+    app.execute_contract(
+        Addr::unchecked(user),
+        market.clone(),
+        &ExecuteMsg::ClaimWinnings {
+            receiver: None,
+        },
+        &positions,
+    )
+}
+
+pub fn th_query_market(app: &MarketApp, market: &Addr) -> Market {
+    app.wrap().query_wasm_smart(market, &QueryMsg::Market {}).unwrap()
+}
+
+/// Position denoms of the market the user holds, which are handed in to claim
+/// when positions are tokenized
+pub fn th_positions(app: &MarketApp, market: &Addr, user: &str) -> Vec<Coin> {
+    let prefix = format!("factory/{}/", market);
+    let balances = app.wrap().query_all_balances(user).unwrap();
+    balances.into_iter().filter(|balance| balance.denom.starts_with(&prefix)).collect()
+}
+
+/// App where each user starts out with `amount` of the market denom
+pub fn th_app(users: &[&str], amount: u128) -> MarketApp {
+    #[cfg(not(feature = "tokenfactory"))]
+    let builder = AppBuilder::new();
+    #[cfg(feature = "tokenfactory")]
+    let builder = BasicAppBuilder::<TokenFactoryMsg, Empty>::new_custom().with_custom(TokenFactory);
+
+    builder.build(|router, _, storage| {
+        for user in users {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(*user), coins(amount, DENOM))
+                .unwrap();
+        }
+    })
+}
+
+pub fn th_instantiate(
+    app: &mut MarketApp,
+    msg: &InstantiateMsg,
+    funds: &[Coin],
+) -> anyhow::Result<Addr> {
+    let code_id =
+        app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query).with_sudo(sudo)));
+    app.instantiate_contract(code_id, Addr::unchecked(ADMIN), msg, funds, "market", None)
+}
+
+pub fn th_balance(app: &MarketApp, address: &str) -> u128 {
+    app.wrap().query_balance(address, DENOM).unwrap().amount.u128()
+}
+
+pub fn th_set_time(app: &mut MarketApp, timestamp: u64) {
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(timestamp - block.time.seconds())
+    });
+}
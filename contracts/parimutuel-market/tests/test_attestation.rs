@@ -0,0 +1,188 @@
+use cosmwasm_std::{to_json_vec, Addr, Binary};
+use cw_multi_test::Executor;
+use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature};
+use sha2::{Digest, Sha256};
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg},
+    state::{KeyType, MarketResult, ProviderKey, Resolution, ScoreResult},
+    Attestation,
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_query_market, th_set_time, MarketApp,
+};
+
+fn secp256k1_key(seed: u8) -> k256::ecdsa::SigningKey {
+    k256::ecdsa::SigningKey::from_slice(&[seed; 32]).unwrap()
+}
+
+fn ed25519_key(seed: u8) -> ed25519_zebra::SigningKey {
+    ed25519_zebra::SigningKey::from([seed; 32])
+}
+
+fn setup() -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&["alice", "bob"], 1_000);
+
+    let secp256k1_public_key =
+        secp256k1_key(1).verifying_key().to_encoded_point(true).as_bytes().to_vec();
+    let ed25519_public_key: [u8; 32] = ed25519_zebra::VerificationKey::from(&ed25519_key(2)).into();
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            resolution: Some(Resolution::ATTESTATION {
+                providers: vec![
+                    ProviderKey {
+                        key_type: KeyType::SECP256K1,
+                        public_key: Binary::from(secp256k1_public_key),
+                    },
+                    ProviderKey {
+                        key_type: KeyType::ED25519,
+                        public_key: Binary::from(ed25519_public_key.to_vec()),
+                    },
+                ],
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 100).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 40 * 60);
+
+    (app, market, start_timestamp)
+}
+
+/// Hash a data provider signs to attest a home win
+fn attestation_hash(contract: &str, market_id: &str, timestamp: u64) -> Vec<u8> {
+    let attestation = Attestation {
+        contract: contract.to_string(),
+        market_id: market_id.to_string(),
+        result: ScoreResult::Result(MarketResult::HOME),
+        timestamp,
+    };
+    Sha256::digest(to_json_vec(&attestation).unwrap()).to_vec()
+}
+
+fn sign_secp256k1(seed: u8, hash: &[u8]) -> Binary {
+    let signature: Signature = secp256k1_key(seed).sign_prehash(hash).unwrap();
+    Binary::from(signature.to_bytes().to_vec())
+}
+
+fn sign_ed25519(seed: u8, hash: &[u8]) -> Binary {
+    let signature: [u8; 64] = ed25519_key(seed).sign(hash).into();
+    Binary::from(signature.to_vec())
+}
+
+fn score(
+    app: &mut MarketApp,
+    market: &Addr,
+    market_id: &str,
+    timestamp: u64,
+    signature: Binary,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked("anyone"),
+        market.clone(),
+        &ExecuteMsg::ScoreWithAttestation {
+            result: ScoreResult::Result(MarketResult::HOME),
+            market_id: market_id.to_string(),
+            timestamp,
+            signature,
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn secp256k1_attestation_scores_the_market() {
+    let (mut app, market, start_timestamp) = setup();
+    let timestamp = start_timestamp + 35 * 60;
+
+    let signature = sign_secp256k1(1, &attestation_hash(market.as_str(), "game-1", timestamp));
+    score(&mut app, &market, "game-1", timestamp, signature).unwrap();
+
+    assert_eq!(th_query_market(&app, &market).result, Some(MarketResult::HOME));
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), 1_100);
+}
+
+#[test]
+fn ed25519_attestation_scores_the_market() {
+    let (mut app, market, start_timestamp) = setup();
+    let timestamp = start_timestamp + 35 * 60;
+
+    let signature = sign_ed25519(2, &attestation_hash(market.as_str(), "game-1", timestamp));
+    score(&mut app, &market, "game-1", timestamp, signature).unwrap();
+
+    assert_eq!(th_query_market(&app, &market).result, Some(MarketResult::HOME));
+}
+
+#[test]
+fn invalid_attestations_are_rejected() {
+    let (mut app, market, start_timestamp) = setup();
+    let timestamp = start_timestamp + 35 * 60;
+    let now = app.block_info().time.seconds();
+
+    let cases = [
+        // Signed and submitted for another market
+        (
+            "game-2",
+            timestamp,
+            sign_secp256k1(1, &attestation_hash(market.as_str(), "game-2", timestamp)),
+        ),
+        // Signed for another contract
+        (
+            "game-1",
+            timestamp,
+            sign_secp256k1(1, &attestation_hash("contract9", "game-1", timestamp)),
+        ),
+        // Timestamped in the future
+        ("game-1", now + 1, sign_ed25519(2, &attestation_hash(market.as_str(), "game-1", now + 1))),
+        // Timestamped before the match
+        (
+            "game-1",
+            start_timestamp - 1,
+            sign_ed25519(2, &attestation_hash(market.as_str(), "game-1", start_timestamp - 1)),
+        ),
+        // Signed by keys that are not providers of the market
+        (
+            "game-1",
+            timestamp,
+            sign_secp256k1(3, &attestation_hash(market.as_str(), "game-1", timestamp)),
+        ),
+        (
+            "game-1",
+            timestamp,
+            sign_ed25519(4, &attestation_hash(market.as_str(), "game-1", timestamp)),
+        ),
+    ];
+
+    for (market_id, timestamp, signature) in cases {
+        let err = score(&mut app, &market, market_id, timestamp, signature).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidAttestation {});
+    }
+
+    assert_eq!(th_query_market(&app, &market).result, None);
+}
+
+#[test]
+fn attestation_cannot_be_replayed_once_closed() {
+    let (mut app, market, start_timestamp) = setup();
+    let timestamp = start_timestamp + 35 * 60;
+
+    let signature = sign_secp256k1(1, &attestation_hash(market.as_str(), "game-1", timestamp));
+    score(&mut app, &market, "game-1", timestamp, signature.clone()).unwrap();
+
+    let err = score(&mut app, &market, "game-1", timestamp, signature).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketNotActive {});
+}
@@ -0,0 +1,186 @@
+use cosmwasm_std::{coins, Addr, Decimal, Uint128};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{MarketResult, MarketType},
+    BetHistoryPage,
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_instantiate, th_instantiate_msg, th_place_bet, MarketApp, ADMIN, DENOM,
+};
+
+fn setup() -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&["alice", "bob"], 1_000);
+    let now = app.block_info().time.seconds();
+    let market = th_instantiate(&mut app, &th_instantiate_msg(now + 24 * 60 * 60), &[]).unwrap();
+
+    (app, market, now)
+}
+
+/// Places a bet, then moves a minute ahead
+fn place_bet(app: &mut MarketApp, market: &Addr, user: &str, result: MarketResult, amount: u128) {
+    th_place_bet(app, market, user, result, amount).unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(60));
+}
+
+fn bet_history(
+    app: &MarketApp,
+    market: &Addr,
+    address: Option<&str>,
+    result: Option<MarketResult>,
+    time_range: (Option<u64>, Option<u64>),
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> (Vec<u64>, Option<u64>) {
+    let page: BetHistoryPage = app
+        .wrap()
+        .query_wasm_smart(
+            market,
+            &QueryMsg::BetHistory {
+                address: address.map(Addr::unchecked),
+                result,
+                start_time: time_range.0,
+                end_time: time_range.1,
+                start_after,
+                limit,
+            },
+        )
+        .unwrap();
+    (page.bets.into_iter().map(|bet| bet.id).collect(), page.next_start_after)
+}
+
+#[test]
+fn every_bet_is_recorded_with_its_odds() {
+    let (mut app, market, now) = setup();
+
+    place_bet(&mut app, &market, "alice", MarketResult::HOME, 100);
+    place_bet(&mut app, &market, "alice", MarketResult::HOME, 100);
+    place_bet(&mut app, &market, "bob", MarketResult::AWAY, 200);
+
+    let page: BetHistoryPage = app
+        .wrap()
+        .query_wasm_smart(
+            &market,
+            &QueryMsg::BetHistory {
+                address: None,
+                result: None,
+                start_time: None,
+                end_time: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let bets = page.bets;
+
+    assert_eq!(bets.len(), 3);
+    assert_eq!(page.next_start_after, None);
+    assert_eq!(bets[1].bettor, Addr::unchecked("alice"));
+    assert_eq!(bets[1].stake, 100);
+    assert_eq!(bets[1].timestamp, now + 60);
+    assert_eq!(bets[1].odds, Decimal::one());
+    assert_eq!(bets[2].odds, Decimal::from_ratio(400_u128, 200_u128));
+}
+
+#[test]
+fn bet_history_filters_and_paginates() {
+    let (mut app, market, now) = setup();
+
+    place_bet(&mut app, &market, "alice", MarketResult::HOME, 100);
+    place_bet(&mut app, &market, "bob", MarketResult::AWAY, 100);
+    place_bet(&mut app, &market, "alice", MarketResult::AWAY, 100);
+    place_bet(&mut app, &market, "bob", MarketResult::HOME, 100);
+
+    assert_eq!(
+        bet_history(&app, &market, Some("alice"), None, (None, None), None, None),
+        (vec![1, 3], None)
+    );
+    assert_eq!(
+        bet_history(&app, &market, None, Some(MarketResult::AWAY), (None, None), None, None),
+        (vec![2, 3], None)
+    );
+    assert_eq!(
+        bet_history(&app, &market, Some("bob"), Some(MarketResult::HOME), (None, None), None, None),
+        (vec![4], None)
+    );
+    assert_eq!(
+        bet_history(&app, &market, None, None, (Some(now + 60), Some(now + 180)), None, None),
+        (vec![2, 3], None)
+    );
+    assert_eq!(
+        bet_history(&app, &market, None, None, (None, None), Some(1), Some(2)),
+        (vec![2, 3], Some(3))
+    );
+}
+
+#[test]
+fn bet_history_scan_is_bounded() {
+    let (mut app, market, now) = setup();
+
+    for _ in 0..305 {
+        place_bet(&mut app, &market, "alice", MarketResult::HOME, 1);
+    }
+
+    // Nothing matches, the first page stops after the scan limit
+    let range = (Some(now + 400 * 60), None);
+    assert_eq!(bet_history(&app, &market, None, None, range, None, None), (vec![], Some(300)));
+    assert_eq!(bet_history(&app, &market, None, None, range, Some(300), None), (vec![], None));
+
+    // The last bet is found when resuming
+    let range = (Some(now + 304 * 60), None);
+    assert_eq!(bet_history(&app, &market, None, None, range, None, None), (vec![], Some(300)));
+    assert_eq!(bet_history(&app, &market, None, None, range, Some(300), None), (vec![305], None));
+}
+
+#[test]
+fn lmsr_buys_are_recorded_at_their_cost() {
+    let mut app = th_app(&[ADMIN, "alice"], 1_000);
+    let now = app.block_info().time.seconds();
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            market_type: Some(MarketType::LMSR {
+                liquidity: Uint128::new(100),
+            }),
+            ..th_instantiate_msg(now + 24 * 60 * 60)
+        },
+        &coins(100, DENOM),
+    )
+    .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked("alice"),
+        market.clone(),
+        &ExecuteMsg::Buy {
+            result: MarketResult::HOME,
+            shares: Uint128::new(10),
+            max_cost: Uint128::new(10),
+        },
+        &coins(10, DENOM),
+    )
+    .unwrap();
+
+    let (bets, _) = bet_history(&app, &market, Some("alice"), None, (None, None), None, None);
+    assert_eq!(bets, [1]);
+
+    let page: BetHistoryPage = app
+        .wrap()
+        .query_wasm_smart(
+            &market,
+            &QueryMsg::BetHistory {
+                address: None,
+                result: None,
+                start_time: None,
+                end_time: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let cost = 1_000 - th_balance(&app, "alice");
+    assert_eq!(page.bets[0].result, MarketResult::HOME);
+    assert_eq!(page.bets[0].stake, cost);
+    assert_eq!(page.bets[0].odds, Decimal::from_ratio(10_u128, cost));
+}
@@ -0,0 +1,215 @@
+// Bet slips are rejected when positions are tokenized
+#![cfg(not(feature = "tokenfactory"))]
+
+use cosmwasm_std::{Addr, Empty};
+use cw721::OwnerOfResponse;
+use cw_multi_test::{ContractWrapper, Executor};
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{BetSlip, MarketResult},
+    SlipInfo,
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_score, th_set_time, MarketApp, ADMIN,
+};
+
+const BALANCE: u128 = 10_000;
+
+/// CW721 collection holding the bet slips as its token extension
+mod slip_collection {
+    use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult};
+    use cw721_base::{ContractError, Cw721Contract, ExecuteMsg, InstantiateMsg, QueryMsg};
+    use vendetta_markets_parimutuel_market::state::BetSlip;
+
+    pub fn instantiate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        Cw721Contract::<BetSlip, Empty, Empty, Empty>::default().instantiate(deps, env, info, msg)
+    }
+
+    pub fn execute(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg<BetSlip, Empty>,
+    ) -> Result<Response, ContractError> {
+        Cw721Contract::<BetSlip, Empty, Empty, Empty>::default().execute(deps, env, info, msg)
+    }
+
+    pub fn query(deps: Deps, env: Env, msg: QueryMsg<Empty>) -> StdResult<Binary> {
+        Cw721Contract::<BetSlip, Empty, Empty, Empty>::default().query(deps, env, msg)
+    }
+}
+
+fn setup() -> (MarketApp, Addr, Addr, u64) {
+    let mut app = th_app(&["alice", "bob", "carol"], BALANCE);
+
+    let collection_code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+        slip_collection::execute,
+        slip_collection::instantiate,
+        slip_collection::query,
+    )));
+    let collection = app
+        .instantiate_contract(
+            collection_code_id,
+            Addr::unchecked(ADMIN),
+            &cw721_base::InstantiateMsg {
+                name: "Bet slips".to_string(),
+                symbol: "SLIP".to_string(),
+                // The market is the next contract to be instantiated
+                minter: "contract1".to_string(),
+            },
+            &[],
+            "slips",
+            None,
+        )
+        .unwrap();
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            slip_collection: Some(collection.to_string()),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(market, Addr::unchecked("contract1"));
+
+    (app, market, collection, start_timestamp)
+}
+
+fn slips_by_owner(app: &MarketApp, market: &Addr, owner: &str) -> Vec<SlipInfo> {
+    app.wrap()
+        .query_wasm_smart(
+            market,
+            &QueryMsg::SlipsByOwner {
+                owner: Addr::unchecked(owner),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap()
+}
+
+fn transfer_slip(app: &mut MarketApp, collection: &Addr, owner: &str, recipient: &str, id: &str) {
+    app.execute_contract(
+        Addr::unchecked(owner),
+        collection.clone(),
+        &cw721_base::ExecuteMsg::<BetSlip, Empty>::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: id.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn claim_slips(
+    app: &mut MarketApp,
+    market: &Addr,
+    sender: &str,
+    ids: &[&str],
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(sender),
+        market.clone(),
+        &ExecuteMsg::ClaimSlips {
+            token_ids: ids.iter().map(|id| id.to_string()).collect(),
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn slip_is_minted_for_every_bet() {
+    let (mut app, market, collection, _) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "alice", MarketResult::AWAY, 500).unwrap();
+
+    let slips = slips_by_owner(&app, &market, "alice");
+    assert_eq!(
+        slips.iter().map(|slip| slip.token_id.as_str()).collect::<Vec<_>>(),
+        ["game-1-1", "game-1-2"]
+    );
+    assert_eq!(slips[0].slip.result, MarketResult::HOME);
+    assert_eq!(slips[0].slip.stake, 1_000);
+    assert_eq!(slips[1].slip.result, MarketResult::AWAY);
+    assert_eq!(slips[1].slip.stake, 500);
+    assert!(slips.iter().all(|slip| !slip.claimed));
+
+    let owner: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &collection,
+            &cw721_base::QueryMsg::<Empty>::OwnerOf {
+                token_id: "game-1-1".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(owner.owner, "alice");
+}
+
+#[test]
+fn slip_holder_is_paid_after_a_transfer() {
+    let (mut app, market, collection, start_timestamp) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 1_000).unwrap();
+    transfer_slip(&mut app, &collection, "alice", "carol", "game-1-1");
+    assert!(slips_by_owner(&app, &market, "alice").is_empty());
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    // Anyone can claim a slip, the payout goes to whoever holds it
+    claim_slips(&mut app, &market, "bob", &["game-1-1"]).unwrap();
+    assert_eq!(th_balance(&app, "carol"), BALANCE + 2_000);
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 1_000);
+
+    let slips = slips_by_owner(&app, &market, "carol");
+    assert_eq!(slips.len(), 1);
+    assert!(slips[0].claimed);
+}
+
+#[test]
+fn slips_cannot_be_claimed_twice() {
+    let (mut app, market, collection, start_timestamp) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 2_000).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    claim_slips(&mut app, &market, "alice", &["game-1-1"]).unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 2_000 + 2_000);
+
+    // Neither again, nor after changing hands, nor along with an unclaimed slip
+    transfer_slip(&mut app, &collection, "alice", "carol", "game-1-1");
+    for ids in [&["game-1-1"][..], &["game-1-2", "game-1-1"]] {
+        let err = claim_slips(&mut app, &market, "carol", ids).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ClaimAlreadyMade {});
+    }
+
+    // Nor through a regular claim of the bettor
+    let err = th_claim_winnings(&mut app, &market, "alice").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PositionsTokenized {});
+
+    claim_slips(&mut app, &market, "alice", &["game-1-2"]).unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE + 2_000);
+    assert_eq!(th_balance(&app, "carol"), BALANCE);
+    assert_eq!(th_balance(&app, market.as_str()), 0);
+}
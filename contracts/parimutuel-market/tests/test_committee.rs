@@ -0,0 +1,133 @@
+use cosmwasm_std::Addr;
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg},
+    state::{MarketResult, Resolution, ScoreResult, Status},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_query_market, th_set_time, MarketApp,
+};
+
+const VOTING_PERIOD: u64 = 60 * 60;
+
+fn setup() -> (MarketApp, Addr) {
+    let mut app = th_app(&["alice", "bob"], 1_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            resolution: Some(Resolution::COMMITTEE {
+                resolvers: ["resolver1", "resolver2", "resolver3"]
+                    .into_iter()
+                    .map(Addr::unchecked)
+                    .collect(),
+                threshold: 2,
+                voting_period: VOTING_PERIOD,
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 100).unwrap();
+
+    // Voting opens once the market becomes scoreable, 30 minutes after its start
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+
+    (app, market)
+}
+
+fn vote(
+    app: &mut MarketApp,
+    market: &Addr,
+    resolver: &str,
+    result: MarketResult,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(resolver),
+        market.clone(),
+        &ExecuteMsg::VoteResult {
+            result: ScoreResult::Result(result),
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn force_cancel(app: &mut MarketApp, market: &Addr) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked("anyone"),
+        market.clone(),
+        &ExecuteMsg::ForceCancel {},
+        &[],
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn threshold_of_matching_votes_scores_the_market() {
+    let (mut app, market) = setup();
+
+    let err = vote(&mut app, &market, "alice", MarketResult::HOME).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {});
+
+    vote(&mut app, &market, "resolver1", MarketResult::HOME).unwrap();
+    assert_eq!(th_query_market(&app, &market).status, Status::ACTIVE);
+
+    let err = vote(&mut app, &market, "resolver1", MarketResult::HOME).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::AlreadyVoted {});
+
+    vote(&mut app, &market, "resolver2", MarketResult::HOME).unwrap();
+
+    let market_state = th_query_market(&app, &market);
+    assert_eq!(market_state.status, Status::CLOSED);
+    assert_eq!(market_state.result, Some(MarketResult::HOME));
+
+    let err = vote(&mut app, &market, "resolver3", MarketResult::AWAY).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketNotActive {});
+}
+
+#[test]
+fn conflicting_votes_wait_for_a_threshold() {
+    let (mut app, market) = setup();
+
+    vote(&mut app, &market, "resolver1", MarketResult::HOME).unwrap();
+    vote(&mut app, &market, "resolver2", MarketResult::AWAY).unwrap();
+    assert_eq!(th_query_market(&app, &market).result, None);
+
+    vote(&mut app, &market, "resolver3", MarketResult::AWAY).unwrap();
+    assert_eq!(th_query_market(&app, &market).result, Some(MarketResult::AWAY));
+
+    th_claim_winnings(&mut app, &market, "bob").unwrap();
+    assert_eq!(th_balance(&app, "bob"), 1_100);
+}
+
+#[test]
+fn market_without_quorum_is_cancelled_once_voting_closes() {
+    let (mut app, market) = setup();
+
+    vote(&mut app, &market, "resolver1", MarketResult::HOME).unwrap();
+
+    let err = force_cancel(&mut app, &market).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::DeadlineNotReached {});
+
+    app.update_block(|block| block.time = block.time.plus_seconds(VOTING_PERIOD));
+
+    let err = vote(&mut app, &market, "resolver2", MarketResult::HOME).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::VotingClosed {});
+
+    force_cancel(&mut app, &market).unwrap();
+    assert_eq!(th_query_market(&app, &market).status, Status::CANCELLED);
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    th_claim_winnings(&mut app, &market, "bob").unwrap();
+    assert_eq!(th_balance(&app, "alice"), 1_000);
+    assert_eq!(th_balance(&app, "bob"), 1_000);
+}
@@ -0,0 +1,303 @@
+#[cfg(not(feature = "tokenfactory"))]
+use cosmwasm_std::Decimal;
+use cosmwasm_std::{
+    coins, to_json_binary, Addr, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Uint128,
+};
+use cw_multi_test::{ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ComplianceQueryMsg, ExecuteMsg, InstantiateMsg, IsAllowedResponse},
+    state::{MarketResult, MarketType},
+};
+
+mod helpers;
+use helpers::{th_app, th_instantiate, th_instantiate_msg, MarketApp, ADMIN, DENOM};
+
+/// Mock compliance registry denying a list of addresses, which can be replaced at any time
+mod mock_registry {
+    use super::*;
+
+    const DENIED: Item<Vec<String>> = Item::new("denied");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: Vec<String>,
+    ) -> StdResult<Response> {
+        DENIED.save(deps.storage, &msg)?;
+        Ok(Response::new())
+    }
+
+    pub fn execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: Vec<String>,
+    ) -> StdResult<Response> {
+        DENIED.save(deps.storage, &msg)?;
+        Ok(Response::new())
+    }
+
+    pub fn query(deps: Deps, _env: Env, msg: ComplianceQueryMsg) -> StdResult<Binary> {
+        match msg {
+            ComplianceQueryMsg::IsAllowed {
+                address,
+            } => to_json_binary(&IsAllowedResponse {
+                allowed: !DENIED.load(deps.storage)?.contains(&address),
+            }),
+        }
+    }
+}
+
+fn setup(denied: &[&str]) -> (MarketApp, Addr, Addr) {
+    setup_market(denied, None, &[])
+}
+
+fn setup_market(
+    denied: &[&str],
+    market_type: Option<MarketType>,
+    funds: &[Coin],
+) -> (MarketApp, Addr, Addr) {
+    let mut app = th_app(&[ADMIN, "alice", "bob", "mallory"], 1_000);
+
+    let registry_code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+        mock_registry::execute,
+        mock_registry::instantiate,
+        mock_registry::query,
+    )));
+    let registry = app
+        .instantiate_contract(
+            registry_code_id,
+            Addr::unchecked(ADMIN),
+            &denied.iter().map(|address| address.to_string()).collect::<Vec<String>>(),
+            &[],
+            "registry",
+            None,
+        )
+        .unwrap();
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            compliance_contract: Some(registry.to_string()),
+            market_type,
+            ..th_instantiate_msg(start_timestamp)
+        },
+        funds,
+    )
+    .unwrap();
+
+    (app, registry, market)
+}
+
+fn place_bet(
+    app: &mut MarketApp,
+    market: &Addr,
+    user: &str,
+    receiver: Option<&str>,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(user),
+        market.clone(),
+        &ExecuteMsg::PlaceBet {
+            result: MarketResult::HOME,
+            receiver: receiver.map(Addr::unchecked),
+            referrer: None,
+        },
+        &coins(100, DENOM),
+    )
+    .map(|_| ())
+}
+
+fn update_blocklist(app: &mut MarketApp, market: &Addr, add: &[&str], remove: &[&str]) {
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::UpdateBlocklist {
+            add: add.iter().map(|address| address.to_string()).collect(),
+            remove: remove.iter().map(|address| address.to_string()).collect(),
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn allowed_address_can_bet() {
+    let (mut app, _, market) = setup(&["mallory"]);
+
+    place_bet(&mut app, &market, "alice", None).unwrap();
+    place_bet(&mut app, &market, "alice", Some("bob")).unwrap();
+}
+
+#[test]
+fn registry_denied_sender_is_rejected() {
+    let (mut app, _, market) = setup(&["mallory"]);
+
+    let err = place_bet(&mut app, &market, "mallory", None).unwrap_err();
+
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AddressNotAllowed("mallory".to_string())
+    );
+}
+
+#[test]
+fn registry_denied_receiver_is_rejected() {
+    let (mut app, _, market) = setup(&["mallory"]);
+
+    let err = place_bet(&mut app, &market, "alice", Some("mallory")).unwrap_err();
+
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AddressNotAllowed("mallory".to_string())
+    );
+}
+
+#[test]
+fn registry_changes_apply_to_the_next_bet() {
+    let (mut app, registry, market) = setup(&[]);
+
+    place_bet(&mut app, &market, "mallory", None).unwrap();
+
+    app.execute_contract(Addr::unchecked(ADMIN), registry, &vec!["mallory".to_string()], &[])
+        .unwrap();
+
+    place_bet(&mut app, &market, "mallory", None).unwrap_err();
+}
+
+#[test]
+fn blocklisted_address_is_rejected_until_removed() {
+    let (mut app, _, market) = setup(&[]);
+
+    update_blocklist(&mut app, &market, &["bob"], &[]);
+
+    let err = place_bet(&mut app, &market, "bob", None).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AddressNotAllowed("bob".to_string())
+    );
+    place_bet(&mut app, &market, "alice", Some("bob")).unwrap_err();
+
+    update_blocklist(&mut app, &market, &[], &["bob"]);
+
+    place_bet(&mut app, &market, "bob", None).unwrap();
+}
+
+// Tokenized positions change hands through the bank module instead
+#[cfg(not(feature = "tokenfactory"))]
+#[test]
+fn denied_buyer_cannot_fill_sell_order() {
+    let (mut app, _, market) = setup(&["mallory"]);
+
+    place_bet(&mut app, &market, "alice", None).unwrap();
+    app.execute_contract(
+        Addr::unchecked("alice"),
+        market.clone(),
+        &ExecuteMsg::CreateSellOrder {
+            result: MarketResult::HOME,
+            amount: Uint128::new(100),
+            price: Decimal::one(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("mallory"),
+            market.clone(),
+            &ExecuteMsg::FillSellOrder {
+                order_id: 1,
+                amount: Uint128::new(100),
+            },
+            &coins(100, DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AddressNotAllowed("mallory".to_string())
+    );
+}
+
+#[cfg(not(feature = "tokenfactory"))]
+#[test]
+fn denied_recipient_cannot_receive_position() {
+    let (mut app, _, market) = setup(&["mallory"]);
+
+    place_bet(&mut app, &market, "alice", None).unwrap();
+    update_blocklist(&mut app, &market, &["bob"], &[]);
+
+    for recipient in ["mallory", "bob"] {
+        let err = app
+            .execute_contract(
+                Addr::unchecked("alice"),
+                market.clone(),
+                &ExecuteMsg::TransferPosition {
+                    outcome: MarketResult::HOME,
+                    amount: Uint128::new(100),
+                    recipient: Addr::unchecked(recipient),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::AddressNotAllowed(recipient.to_string())
+        );
+    }
+}
+
+#[test]
+fn denied_buyer_cannot_buy_lmsr_shares() {
+    let (mut app, _, market) = setup_market(
+        &["mallory"],
+        Some(MarketType::LMSR {
+            liquidity: Uint128::new(100),
+        }),
+        &coins(100, DENOM),
+    );
+
+    let buy = |app: &mut MarketApp, user: &str| {
+        app.execute_contract(
+            Addr::unchecked(user),
+            market.clone(),
+            &ExecuteMsg::Buy {
+                result: MarketResult::HOME,
+                shares: Uint128::new(10),
+                max_cost: Uint128::new(10),
+            },
+            &coins(10, DENOM),
+        )
+    };
+
+    buy(&mut app, "alice").unwrap();
+    let err = buy(&mut app, "mallory").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AddressNotAllowed("mallory".to_string())
+    );
+}
+
+#[test]
+fn only_admin_updates_blocklist() {
+    let (mut app, _, market) = setup(&[]);
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("alice"),
+            market,
+            &ExecuteMsg::UpdateBlocklist {
+                add: vec!["bob".to_string()],
+                remove: vec![],
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {});
+}
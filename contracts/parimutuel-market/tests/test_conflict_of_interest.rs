@@ -0,0 +1,256 @@
+#[cfg(not(feature = "tokenfactory"))]
+use cosmwasm_std::Decimal;
+use cosmwasm_std::{coins, Addr, Coin, Uint128};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{MarketResult, MarketType, Resolution},
+};
+
+mod helpers;
+use helpers::{th_app, th_instantiate, th_instantiate_msg, MarketApp, ADMIN, DENOM, TREASURY};
+
+const USERS: [&str; 7] = [ADMIN, TREASURY, "arbiter", "resolver1", "resolver2", "insider", "alice"];
+
+/// Markets resolved by an arbiter and by a committee, each with an associated
+/// address, along with the addresses controlling them
+fn setup(market_type: Option<MarketType>, funds: &[Coin]) -> Vec<(MarketApp, Addr, Vec<&str>)> {
+    let resolutions = [
+        (
+            Resolution::OPTIMISTIC {
+                bond: Uint128::new(100),
+                challenge_window: 60 * 60,
+                arbiter: Addr::unchecked("arbiter"),
+            },
+            vec![ADMIN, TREASURY, "arbiter", "insider"],
+        ),
+        (
+            Resolution::COMMITTEE {
+                resolvers: vec![Addr::unchecked("resolver1"), Addr::unchecked("resolver2")],
+                threshold: 2,
+                voting_period: 60 * 60,
+            },
+            vec!["resolver1", "resolver2", "insider"],
+        ),
+    ];
+
+    resolutions
+        .into_iter()
+        .map(|(resolution, privileged)| {
+            let mut app = th_app(&USERS, 10_000);
+
+            let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+            let market = th_instantiate(
+                &mut app,
+                &InstantiateMsg {
+                    market_type: market_type.clone(),
+                    resolution: Some(resolution),
+                    ..th_instantiate_msg(start_timestamp)
+                },
+                funds,
+            )
+            .unwrap();
+            update_associated(&mut app, &market, &["insider"], &[]).unwrap();
+
+            (app, market, privileged)
+        })
+        .collect()
+}
+
+fn update_associated(
+    app: &mut MarketApp,
+    market: &Addr,
+    add: &[&str],
+    remove: &[&str],
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::UpdateAssociatedAddresses {
+            add: add.iter().map(|address| address.to_string()).collect(),
+            remove: remove.iter().map(|address| address.to_string()).collect(),
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn place_bet(
+    app: &mut MarketApp,
+    market: &Addr,
+    user: &str,
+    receiver: Option<&str>,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(user),
+        market.clone(),
+        &ExecuteMsg::PlaceBet {
+            result: MarketResult::HOME,
+            receiver: receiver.map(Addr::unchecked),
+            referrer: None,
+        },
+        &coins(100, DENOM),
+    )
+    .map(|_| ())
+}
+
+fn assert_conflict_of_interest(err: anyhow::Error, address: &str) {
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ConflictOfInterest(address.to_string())
+    );
+}
+
+#[test]
+fn privileged_addresses_cannot_bet() {
+    for (mut app, market, privileged) in setup(None, &[]) {
+        for address in privileged {
+            let err = place_bet(&mut app, &market, address, None).unwrap_err();
+            assert_conflict_of_interest(err, address);
+        }
+
+        place_bet(&mut app, &market, "alice", None).unwrap();
+    }
+}
+
+#[test]
+fn privileged_addresses_cannot_receive_bets() {
+    for (mut app, market, privileged) in setup(None, &[]) {
+        for address in privileged {
+            let err = place_bet(&mut app, &market, "alice", Some(address)).unwrap_err();
+            assert_conflict_of_interest(err, address);
+        }
+    }
+}
+
+#[test]
+fn privileged_addresses_cannot_buy_lmsr_shares() {
+    let market_type = MarketType::LMSR {
+        liquidity: Uint128::new(100),
+    };
+
+    for (mut app, market, privileged) in setup(Some(market_type), &coins(100, DENOM)) {
+        let mut buy = |user: &str| {
+            app.execute_contract(
+                Addr::unchecked(user),
+                market.clone(),
+                &ExecuteMsg::Buy {
+                    result: MarketResult::HOME,
+                    shares: Uint128::new(10),
+                    max_cost: Uint128::new(10),
+                },
+                &coins(10, DENOM),
+            )
+        };
+
+        for address in privileged {
+            assert_conflict_of_interest(buy(address).unwrap_err(), address);
+        }
+        buy("alice").unwrap();
+    }
+}
+
+// Tokenized positions change hands through the bank module instead
+#[cfg(not(feature = "tokenfactory"))]
+#[test]
+fn privileged_addresses_cannot_fill_sell_orders() {
+    for (mut app, market, privileged) in setup(None, &[]) {
+        place_bet(&mut app, &market, "alice", None).unwrap();
+        app.execute_contract(
+            Addr::unchecked("alice"),
+            market.clone(),
+            &ExecuteMsg::CreateSellOrder {
+                result: MarketResult::HOME,
+                amount: Uint128::new(100),
+                price: Decimal::one(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        for address in privileged {
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(address),
+                    market.clone(),
+                    &ExecuteMsg::FillSellOrder {
+                        order_id: 1,
+                        amount: Uint128::new(100),
+                    },
+                    &coins(100, DENOM),
+                )
+                .unwrap_err();
+            assert_conflict_of_interest(err, address);
+        }
+    }
+}
+
+#[cfg(not(feature = "tokenfactory"))]
+#[test]
+fn privileged_addresses_cannot_receive_positions() {
+    for (mut app, market, privileged) in setup(None, &[]) {
+        place_bet(&mut app, &market, "alice", None).unwrap();
+
+        for address in privileged {
+            let err = app
+                .execute_contract(
+                    Addr::unchecked("alice"),
+                    market.clone(),
+                    &ExecuteMsg::TransferPosition {
+                        outcome: MarketResult::HOME,
+                        amount: Uint128::new(100),
+                        recipient: Addr::unchecked(address),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            assert_conflict_of_interest(err, address);
+        }
+    }
+}
+
+#[test]
+fn associated_addresses_can_be_added_and_removed() {
+    let (mut app, market, _) = setup(None, &[]).remove(0);
+    let associated = |app: &MarketApp| -> Vec<Addr> {
+        app.wrap()
+            .query_wasm_smart(
+                &market,
+                &QueryMsg::AssociatedAddresses {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+    };
+
+    update_associated(&mut app, &market, &["alice"], &[]).unwrap();
+    assert_eq!(associated(&app), vec![Addr::unchecked("alice"), Addr::unchecked("insider")]);
+    let err = place_bet(&mut app, &market, "alice", None).unwrap_err();
+    assert_conflict_of_interest(err, "alice");
+
+    update_associated(&mut app, &market, &[], &["alice", "insider"]).unwrap();
+    assert_eq!(associated(&app), Vec::<Addr>::new());
+    place_bet(&mut app, &market, "alice", None).unwrap();
+    place_bet(&mut app, &market, "insider", None).unwrap();
+}
+
+#[test]
+fn only_admin_updates_associated_addresses() {
+    let (mut app, market, _) = setup(None, &[]).remove(0);
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("arbiter"),
+            market,
+            &ExecuteMsg::UpdateAssociatedAddresses {
+                add: vec!["alice".to_string()],
+                remove: vec![],
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {});
+}
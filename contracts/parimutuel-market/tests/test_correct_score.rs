@@ -0,0 +1,114 @@
+use cosmwasm_std::Addr;
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg},
+    state::{MarketResult, MarketType, ScoreResult},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_set_time, MarketApp, ADMIN,
+};
+
+fn setup(max_outcomes: u32) -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&["alice", "bob", "carol"], 1_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            market_type: Some(MarketType::CORRECT_SCORE {
+                max_outcomes,
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn score(
+    app: &mut MarketApp,
+    market: &Addr,
+    home_score: u64,
+    away_score: u64,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::Score {
+            result: Some(ScoreResult::Scores {
+                home_score,
+                away_score,
+            }),
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn exact(home: u64, away: u64) -> MarketResult {
+    MarketResult::SCORE {
+        home,
+        away,
+    }
+}
+
+#[test]
+fn new_scores_are_capped_at_max_outcomes() {
+    let (mut app, market, _) = setup(2);
+
+    th_place_bet(&mut app, &market, "alice", exact(1, 0), 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", exact(0, 0), 100).unwrap();
+
+    let err = th_place_bet(&mut app, &market, "carol", exact(2, 1), 100).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MaxOutcomesReached {});
+
+    // Scores that already have a pool stay open
+    th_place_bet(&mut app, &market, "carol", exact(1, 0), 100).unwrap();
+}
+
+#[test]
+fn exact_score_backers_share_the_pool() {
+    let (mut app, market, start_timestamp) = setup(10);
+
+    th_place_bet(&mut app, &market, "alice", exact(1, 0), 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", exact(0, 0), 100).unwrap();
+    th_place_bet(&mut app, &market, "carol", exact(1, 0), 300).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 2 * 60 * 60);
+    score(&mut app, &market, 1, 0).unwrap();
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    th_claim_winnings(&mut app, &market, "carol").unwrap();
+    assert_eq!(th_balance(&app, "alice"), 1_000 + 25);
+    assert_eq!(th_balance(&app, "carol"), 1_000 + 75);
+
+    let err = th_claim_winnings(&mut app, &market, "bob").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NoWinnings {});
+}
+
+#[test]
+fn unbacked_score_cannot_settle_the_market() {
+    let (mut app, market, start_timestamp) = setup(10);
+
+    th_place_bet(&mut app, &market, "alice", exact(1, 0), 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", exact(0, 0), 100).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 2 * 60 * 60);
+    let err = score(&mut app, &market, 3, 3).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NoWinnings {});
+
+    // Nobody backed the final score, so the market is cancelled and every bet refunded
+    app.execute_contract(Addr::unchecked(ADMIN), market.clone(), &ExecuteMsg::Cancel {}, &[])
+        .unwrap();
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    th_claim_winnings(&mut app, &market, "bob").unwrap();
+
+    assert_eq!(th_balance(&app, "alice"), 1_000);
+    assert_eq!(th_balance(&app, "bob"), 1_000);
+}
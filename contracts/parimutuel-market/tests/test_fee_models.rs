@@ -0,0 +1,128 @@
+use std::str::FromStr;
+
+use cosmwasm_std::{coins, Addr, SignedDecimal, Uint128};
+use cw_multi_test::Executor;
+use proptest::prelude::*;
+use vendetta_markets_parimutuel_market::{
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{FeeModel, HouseSeed, Market, MarketResult, MarketType, ScoreResult, SeedPolicy},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_set_time, MarketApp, ADMIN, DENOM,
+};
+
+const USERS: [&str; 4] = ["alice", "bob", "carol", "dave"];
+
+/// Handicap line and final score settling to each of HOME, AWAY, HALF_AWAY and HALF_HOME
+const SETTLEMENTS: [(&str, u64, u64); 4] =
+    [("-0.25", 2, 1), ("-0.25", 0, 1), ("-0.25", 1, 1), ("0.25", 1, 1)];
+
+fn setup(
+    fee_model: FeeModel,
+    fee_bps: u64,
+    line: &str,
+    seed: Option<HouseSeed>,
+) -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&[ADMIN, "alice", "bob", "carol", "dave"], 1_000_000_000);
+
+    let funds = match &seed {
+        Some(seed) => coins(2 * seed.amount.u128(), DENOM),
+        None => vec![],
+    };
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            fee_bps,
+            fee_model: Some(fee_model),
+            market_type: Some(MarketType::HANDICAP {
+                line: SignedDecimal::from_str(line).unwrap(),
+            }),
+            seed,
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &funds,
+    )
+    .unwrap();
+
+    (app, market, start_timestamp)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    /// Fees on the profit or on the losing pool never take a winner below
+    /// their stake, whether they win it in full or only half of it
+    #[test]
+    fn winners_never_get_back_less_than_their_stake(
+        profit_only in any::<bool>(),
+        fee_bps in 0_u64..=1_000,
+        settlement in 0_usize..SETTLEMENTS.len(),
+        seed in prop::option::of((1_u128..=100_000, any::<bool>())),
+        bets in prop::collection::vec((0_usize..USERS.len(), any::<bool>(), 1_u128..=100_000), 2..20),
+    ) {
+        let fee_model = if profit_only { FeeModel::PROFIT_ONLY } else { FeeModel::LOSING_POOL };
+        let seed = seed.map(|(amount, redistribute)| HouseSeed {
+            amount: Uint128::new(amount),
+            policy: if redistribute { SeedPolicy::REDISTRIBUTE } else { SeedPolicy::RETURN },
+        });
+        let (line, home_score, away_score) = SETTLEMENTS[settlement];
+        let (mut app, market, start_timestamp) = setup(fee_model, fee_bps, line, seed);
+
+        // Both sides need a bet for an unseeded market to be scoreable
+        let mut bets = bets;
+        bets[0].1 = true;
+        bets[1].1 = false;
+
+        let mut stakes = [(0_u128, 0_u128); USERS.len()];
+        for (user, is_home, amount) in bets.iter() {
+            let result = if *is_home { MarketResult::HOME } else { MarketResult::AWAY };
+            th_place_bet(&mut app, &market, USERS[*user], result, *amount).unwrap();
+            if *is_home {
+                stakes[*user].0 += amount;
+            } else {
+                stakes[*user].1 += amount;
+            }
+        }
+
+        th_set_time(&mut app, start_timestamp + 2 * 60 * 60);
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            market.clone(),
+            &ExecuteMsg::Score {
+                result: Some(ScoreResult::Scores { home_score, away_score }),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let market_state: Market =
+            app.wrap().query_wasm_smart(&market, &QueryMsg::Market {}).unwrap();
+        let result = market_state.result.unwrap();
+        let home_wins = matches!(result, MarketResult::HOME | MarketResult::HALF_HOME);
+
+        for (user, (home_stake, away_stake)) in USERS.iter().zip(stakes) {
+            let winning_stake = if home_wins { home_stake } else { away_stake };
+            if winning_stake == 0 {
+                continue;
+            }
+
+            let before = th_balance(&app, user);
+            th_claim_winnings(&mut app, &market, user).unwrap();
+            let payout = th_balance(&app, user) - before;
+
+            prop_assert!(
+                payout >= winning_stake,
+                "{} staked {} on the {} side and got {}",
+                user,
+                winning_stake,
+                result,
+                payout
+            );
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use cosmwasm_std::{coins, Addr, Decimal};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{MarketResult, MarketType},
+    Liability, OutcomeOdds,
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_score, th_set_time, MarketApp, ADMIN, DENOM,
+};
+
+const BALANCE: u128 = 10_000;
+const LIQUIDITY: u128 = 1_000;
+
+/// Fixed odds market with the house liquidity deposited, offering 2.0 on
+/// HOME and 3.0 on AWAY
+fn setup() -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&[ADMIN, "alice", "bob"], BALANCE);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            market_type: Some(MarketType::FIXED_ODDS),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::DepositLiquidity {},
+        &coins(LIQUIDITY, DENOM),
+    )
+    .unwrap();
+    update_odds(&mut app, &market, ADMIN, &[(MarketResult::HOME, 2), (MarketResult::AWAY, 3)])
+        .unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn update_odds(
+    app: &mut MarketApp,
+    market: &Addr,
+    sender: &str,
+    odds: &[(MarketResult, u64)],
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(sender),
+        market.clone(),
+        &ExecuteMsg::UpdateOdds {
+            odds: odds
+                .iter()
+                .map(|(result, odds)| OutcomeOdds {
+                    result: result.clone(),
+                    odds: Decimal::from_ratio(*odds, 1_u64),
+                })
+                .collect(),
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn withdraw_liquidity(app: &mut MarketApp, market: &Addr) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::WithdrawLiquidity {},
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn query_liability(app: &MarketApp, market: &Addr) -> Liability {
+    app.wrap().query_wasm_smart(market, &QueryMsg::Liability {}).unwrap()
+}
+
+#[test]
+fn liability_follows_the_potential_payouts() {
+    let (mut app, market, _) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 500).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 200).unwrap();
+
+    let liability = query_liability(&app, &market);
+    assert_eq!(liability.house_liquidity, LIQUIDITY);
+    assert_eq!(liability.total_stakes, 700);
+
+    let home = &liability.outcomes[0];
+    assert_eq!(home.result, MarketResult::HOME);
+    assert_eq!(home.odds, Some(Decimal::from_ratio(2_u64, 1_u64)));
+    assert_eq!((home.potential_payout, home.liability), (1_000, 300));
+    let away = &liability.outcomes[1];
+    assert_eq!(away.result, MarketResult::AWAY);
+    assert_eq!((away.potential_payout, away.liability), (600, 0));
+}
+
+#[test]
+fn bets_are_locked_in_at_the_odds_they_were_placed_at() {
+    let (mut app, market, start_timestamp) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 500).unwrap();
+    update_odds(&mut app, &market, ADMIN, &[(MarketResult::HOME, 4)]).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::HOME, 100).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    th_claim_winnings(&mut app, &market, "bob").unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 500 + 1_000);
+    assert_eq!(th_balance(&app, "bob"), BALANCE - 100 + 400);
+}
+
+#[test]
+fn only_admin_sets_valid_odds() {
+    let (mut app, market, _) = setup();
+
+    let err = update_odds(&mut app, &market, "alice", &[(MarketResult::HOME, 5)]).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {});
+
+    let err = update_odds(&mut app, &market, ADMIN, &[(MarketResult::HOME, 1)]).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidOdds {});
+
+    let err = update_odds(&mut app, &market, ADMIN, &[(MarketResult::DRAW, 2)]).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidOutcome {});
+}
+
+#[test]
+fn bet_is_rejected_when_the_house_cannot_cover_it() {
+    let (mut app, market, _) = setup();
+
+    // The house covers up to its liquidity and the stakes placed so far
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 500).unwrap();
+    let err = th_place_bet(&mut app, &market, "bob", MarketResult::HOME, 501).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MaxLiabilityExceeded {});
+
+    th_place_bet(&mut app, &market, "bob", MarketResult::HOME, 500).unwrap();
+    let liability = query_liability(&app, &market);
+    assert_eq!(liability.outcomes[0].liability, LIQUIDITY);
+
+    // More liquidity takes more bets
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::DepositLiquidity {},
+        &coins(500, DENOM),
+    )
+    .unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::HOME, 500).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::HOME, 1).unwrap_err();
+}
+
+#[test]
+fn house_only_withdraws_what_winners_are_not_owed() {
+    let (mut app, market, start_timestamp) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 500).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 200).unwrap();
+
+    let err = withdraw_liquidity(&mut app, &market).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketNotClosed {});
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    withdraw_liquidity(&mut app, &market).unwrap();
+    assert_eq!(th_balance(&app, ADMIN), BALANCE - LIQUIDITY + LIQUIDITY + 700 - 1_000);
+    let err = withdraw_liquidity(&mut app, &market).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LiquidityAlreadyWithdrawn {}
+    );
+
+    // What is left is exactly the winnings owed
+    assert_eq!(th_balance(&app, market.as_str()), 1_000);
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 500 + 1_000);
+    assert_eq!(th_balance(&app, market.as_str()), 0);
+}
+
+#[test]
+fn house_withdraws_its_liquidity_when_cancelled() {
+    let (mut app, market, _) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 500).unwrap();
+    app.execute_contract(Addr::unchecked(ADMIN), market.clone(), &ExecuteMsg::Cancel {}, &[])
+        .unwrap();
+
+    withdraw_liquidity(&mut app, &market).unwrap();
+    assert_eq!(th_balance(&app, ADMIN), BALANCE);
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE);
+    assert_eq!(th_balance(&app, market.as_str()), 0);
+}
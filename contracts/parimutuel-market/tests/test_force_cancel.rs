@@ -0,0 +1,116 @@
+use cosmwasm_std::Addr;
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg},
+    state::{default_resolution_deadline, MarketResult, Status},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_query_market, th_score, th_set_time, MarketApp, ADMIN,
+};
+
+/// Plain admin market with bets on both sides
+fn setup(resolution_deadline: Option<u64>) -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&["alice", "bob"], 1_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            resolution_deadline,
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 1_000).unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn force_cancel(app: &mut MarketApp, market: &Addr) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked("anyone"),
+        market.clone(),
+        &ExecuteMsg::ForceCancel {},
+        &[],
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn anyone_cancels_an_admin_market_after_the_default_deadline() {
+    let (mut app, market, start_timestamp) = setup(None);
+    let deadline = start_timestamp + default_resolution_deadline();
+
+    th_set_time(&mut app, deadline - 1);
+    let err = force_cancel(&mut app, &market).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::DeadlineNotReached {});
+
+    th_set_time(&mut app, deadline);
+    force_cancel(&mut app, &market).unwrap();
+    assert_eq!(th_query_market(&app, &market).status, Status::CANCELLED);
+
+    // The admin can no longer score it, and the bettors get their stakes back
+    let err = th_score(&mut app, &market, MarketResult::HOME).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketNotActive {});
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    th_claim_winnings(&mut app, &market, "bob").unwrap();
+    assert_eq!(th_balance(&app, "alice"), 1_000);
+    assert_eq!(th_balance(&app, "bob"), 1_000);
+}
+
+#[test]
+fn custom_deadline_applies() {
+    let (mut app, market, start_timestamp) = setup(Some(2 * 60 * 60));
+
+    th_set_time(&mut app, start_timestamp + 2 * 60 * 60 - 1);
+    force_cancel(&mut app, &market).unwrap_err();
+
+    th_set_time(&mut app, start_timestamp + 2 * 60 * 60);
+    force_cancel(&mut app, &market).unwrap();
+    assert_eq!(th_query_market(&app, &market).status, Status::CANCELLED);
+}
+
+#[test]
+fn deadline_must_leave_time_to_score() {
+    let mut app = th_app(&[], 0);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let err = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            resolution_deadline: Some(30 * 60),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidResolutionDeadline {}
+    );
+}
+
+#[test]
+fn scored_market_cannot_be_cancelled() {
+    let (mut app, market, start_timestamp) = setup(None);
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    th_set_time(&mut app, start_timestamp + default_resolution_deadline());
+    let err = force_cancel(&mut app, &market).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketNotActive {});
+
+    // Nor by the admin
+    let err = app
+        .execute_contract(Addr::unchecked(ADMIN), market.clone(), &ExecuteMsg::Cancel {}, &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketNotActive {});
+}
@@ -0,0 +1,93 @@
+use std::str::FromStr;
+
+use cosmwasm_std::{Addr, SignedDecimal};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{Market, MarketResult, MarketType, ScoreResult},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_set_time, ADMIN, TREASURY,
+};
+
+const BETS: [(&str, MarketResult, u128); 4] = [
+    ("alice", MarketResult::HOME, 101),
+    ("bob", MarketResult::HOME, 33),
+    ("carol", MarketResult::AWAY, 77),
+    ("dave", MarketResult::AWAY, 3),
+];
+
+/// Settles a market on the handicap line with the given score, then claims
+/// every bet and returns what each bettor was paid
+fn settle(line: &str, home_score: u64, away_score: u64) -> (MarketResult, Vec<u128>, u128) {
+    let mut app = th_app(&["alice", "bob", "carol", "dave"], 1_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            fee_bps: 250,
+            market_type: Some(MarketType::HANDICAP {
+                line: SignedDecimal::from_str(line).unwrap(),
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    for (user, result, amount) in BETS {
+        th_place_bet(&mut app, &market, user, result, amount).unwrap();
+    }
+
+    th_set_time(&mut app, start_timestamp + 2 * 60 * 60);
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::Score {
+            result: Some(ScoreResult::Scores {
+                home_score,
+                away_score,
+            }),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let payouts = BETS
+        .iter()
+        .map(|(user, _, amount)| {
+            th_claim_winnings(&mut app, &market, user).unwrap();
+            th_balance(&app, user) + amount - 1_000
+        })
+        .collect();
+
+    let market_state: Market = app.wrap().query_wasm_smart(&market, &QueryMsg::Market {}).unwrap();
+    (market_state.result.unwrap(), payouts, th_balance(&app, TREASURY))
+}
+
+#[test]
+fn half_results_pay_out_at_most_the_pool() {
+    let pool = BETS.iter().map(|(_, _, amount)| amount).sum::<u128>();
+
+    let cases = [
+        ("-0.25", (1, 1), MarketResult::HALF_AWAY),
+        ("0.25", (1, 1), MarketResult::HALF_HOME),
+        ("-0.75", (2, 1), MarketResult::HALF_HOME),
+        ("0.75", (1, 2), MarketResult::HALF_AWAY),
+    ];
+
+    for (line, (home_score, away_score), expected) in cases {
+        let (result, payouts, fee) = settle(line, home_score, away_score);
+        assert_eq!(result, expected, "line {line}");
+        assert!(payouts.iter().sum::<u128>() + fee <= pool, "line {line} pays out {payouts:?}");
+
+        // Every bet gets at least half of its stake back
+        for ((_, _, amount), payout) in BETS.iter().zip(&payouts) {
+            assert!(*payout >= amount / 2, "line {line} pays out {payouts:?}");
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use cosmwasm_std::{coins, Addr, Uint128};
+use cw_multi_test::{AppResponse, Executor};
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg},
+    state::{MarketResult, MarketType},
+};
+
+mod helpers;
+use helpers::{th_app, th_instantiate, th_instantiate_msg, MarketApp, ADMIN, DENOM};
+
+fn setup() -> (MarketApp, Addr) {
+    let mut app = th_app(&[ADMIN, "alice"], 10_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            market_type: Some(MarketType::LMSR {
+                liquidity: Uint128::new(100),
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &coins(100, DENOM),
+    )
+    .unwrap();
+
+    (app, market)
+}
+
+fn buy(app: &mut MarketApp, market: &Addr, shares: u128) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        Addr::unchecked("alice"),
+        market.clone(),
+        &ExecuteMsg::Buy {
+            result: MarketResult::HOME,
+            shares: Uint128::new(shares),
+            max_cost: Uint128::new(100),
+        },
+        &coins(100, DENOM),
+    )
+}
+
+fn sell(app: &mut MarketApp, market: &Addr, shares: u128) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        Addr::unchecked("alice"),
+        market.clone(),
+        &ExecuteMsg::Sell {
+            result: MarketResult::HOME,
+            shares: Uint128::new(shares),
+            min_proceeds: Uint128::zero(),
+        },
+        &[],
+    )
+}
+
+#[test]
+fn zero_shares_are_rejected() {
+    let (mut app, market) = setup();
+
+    let err = buy(&mut app, &market, 0).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidAmount {});
+
+    buy(&mut app, &market, 10).unwrap();
+    let err = sell(&mut app, &market, 0).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidAmount {});
+}
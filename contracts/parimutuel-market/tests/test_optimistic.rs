@@ -0,0 +1,159 @@
+use cosmwasm_std::{coins, Addr, Uint128};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg},
+    state::{MarketResult, Resolution, ScoreResult, Status},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_instantiate, th_instantiate_msg, th_place_bet, th_query_market,
+    th_set_time, MarketApp, DENOM,
+};
+
+const BOND: u128 = 50;
+const CHALLENGE_WINDOW: u64 = 60 * 60;
+
+fn setup() -> (MarketApp, Addr) {
+    let mut app = th_app(&["alice", "bob", "proposer", "disputer"], 1_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            resolution: Some(Resolution::OPTIMISTIC {
+                bond: Uint128::new(BOND),
+                challenge_window: CHALLENGE_WINDOW,
+                arbiter: Addr::unchecked("arbiter"),
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 100).unwrap();
+
+    // Markets become scoreable 30 minutes after their start
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+
+    (app, market)
+}
+
+fn execute(
+    app: &mut MarketApp,
+    market: &Addr,
+    user: &str,
+    msg: ExecuteMsg,
+    bond: u128,
+) -> anyhow::Result<()> {
+    let funds = if bond > 0 {
+        coins(bond, DENOM)
+    } else {
+        vec![]
+    };
+    app.execute_contract(Addr::unchecked(user), market.clone(), &msg, &funds).map(|_| ())
+}
+
+fn propose(result: MarketResult) -> ExecuteMsg {
+    ExecuteMsg::Propose {
+        result: ScoreResult::Result(result),
+    }
+}
+
+fn arbitrate(result: MarketResult) -> ExecuteMsg {
+    ExecuteMsg::Arbitrate {
+        result: ScoreResult::Result(result),
+    }
+}
+
+fn advance(app: &mut MarketApp, seconds: u64) {
+    app.update_block(|block| block.time = block.time.plus_seconds(seconds));
+}
+
+#[test]
+fn undisputed_proposal_is_finalized_after_the_challenge_window() {
+    let (mut app, market) = setup();
+
+    let err =
+        execute(&mut app, &market, "proposer", propose(MarketResult::HOME), BOND - 1).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PaymentError {});
+
+    execute(&mut app, &market, "proposer", propose(MarketResult::HOME), BOND).unwrap();
+    assert_eq!(th_balance(&app, "proposer"), 1_000 - BOND);
+
+    let err =
+        execute(&mut app, &market, "disputer", propose(MarketResult::AWAY), BOND).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ProposalAlreadyMade {});
+
+    let err = execute(&mut app, &market, "anyone", ExecuteMsg::Finalize {}, 0).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ChallengeWindowOpen {});
+
+    advance(&mut app, CHALLENGE_WINDOW);
+
+    let err = execute(&mut app, &market, "disputer", ExecuteMsg::Dispute {}, BOND).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ChallengeWindowClosed {});
+
+    execute(&mut app, &market, "anyone", ExecuteMsg::Finalize {}, 0).unwrap();
+
+    let market_state = th_query_market(&app, &market);
+    assert_eq!(market_state.status, Status::CLOSED);
+    assert_eq!(market_state.result, Some(MarketResult::HOME));
+    assert_eq!(th_balance(&app, "proposer"), 1_000);
+}
+
+#[test]
+fn disputed_proposal_overturned_pays_both_bonds_to_the_disputer() {
+    let (mut app, market) = setup();
+
+    execute(&mut app, &market, "proposer", propose(MarketResult::HOME), BOND).unwrap();
+    execute(&mut app, &market, "disputer", ExecuteMsg::Dispute {}, BOND).unwrap();
+
+    let err = execute(&mut app, &market, "bob", ExecuteMsg::Dispute {}, BOND).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::AlreadyDisputed {});
+
+    // A disputed proposal is never finalized, only the arbiter decides
+    advance(&mut app, CHALLENGE_WINDOW);
+    let err = execute(&mut app, &market, "anyone", ExecuteMsg::Finalize {}, 0).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::AlreadyDisputed {});
+
+    let err = execute(&mut app, &market, "proposer", arbitrate(MarketResult::HOME), 0).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {});
+
+    execute(&mut app, &market, "arbiter", arbitrate(MarketResult::AWAY), 0).unwrap();
+
+    assert_eq!(th_query_market(&app, &market).result, Some(MarketResult::AWAY));
+    assert_eq!(th_balance(&app, "proposer"), 1_000 - BOND);
+    assert_eq!(th_balance(&app, "disputer"), 1_000 + BOND);
+}
+
+#[test]
+fn disputed_proposal_upheld_pays_both_bonds_to_the_proposer() {
+    let (mut app, market) = setup();
+
+    execute(&mut app, &market, "proposer", propose(MarketResult::HOME), BOND).unwrap();
+    execute(&mut app, &market, "disputer", ExecuteMsg::Dispute {}, BOND).unwrap();
+    execute(&mut app, &market, "arbiter", arbitrate(MarketResult::HOME), 0).unwrap();
+
+    assert_eq!(th_query_market(&app, &market).result, Some(MarketResult::HOME));
+    assert_eq!(th_balance(&app, "proposer"), 1_000 + BOND);
+    assert_eq!(th_balance(&app, "disputer"), 1_000 - BOND);
+}
+
+#[test]
+fn pending_bonds_are_refunded_when_the_market_is_cancelled() {
+    let (mut app, market) = setup();
+
+    execute(&mut app, &market, "proposer", propose(MarketResult::HOME), BOND).unwrap();
+    execute(&mut app, &market, "disputer", ExecuteMsg::Dispute {}, BOND).unwrap();
+
+    // Nobody arbitrates before the resolution deadline
+    advance(&mut app, 7 * 24 * 60 * 60);
+    execute(&mut app, &market, "anyone", ExecuteMsg::ForceCancel {}, 0).unwrap();
+
+    assert_eq!(th_query_market(&app, &market).status, Status::CANCELLED);
+    assert_eq!(th_balance(&app, "proposer"), 1_000);
+    assert_eq!(th_balance(&app, "disputer"), 1_000);
+}
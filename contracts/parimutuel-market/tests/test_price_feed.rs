@@ -0,0 +1,264 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult,
+};
+use cw_multi_test::{ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use pyth_sdk_cw::{Price, PriceFeed, PriceFeedResponse, PriceIdentifier, QueryMsg as PythQueryMsg};
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg},
+    state::{default_resolution_deadline, MarketResult, MarketType, Status},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_query_market, th_set_time, MarketApp, ADMIN,
+};
+const PRICE_FEED_ID: &str = "b00b60f88b03a6a625a8d1c048c3f66653edf217439983d037e7222c4e612819";
+
+/// Mock Pyth contract serving a single price feed, which can be replaced at any time
+mod mock_pyth {
+    use super::*;
+
+    const PRICE_FEED: Item<PriceFeed> = Item::new("price_feed");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: PriceFeed,
+    ) -> StdResult<Response> {
+        PRICE_FEED.save(deps.storage, &msg)?;
+        Ok(Response::new())
+    }
+
+    pub fn execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: PriceFeed,
+    ) -> StdResult<Response> {
+        PRICE_FEED.save(deps.storage, &msg)?;
+        Ok(Response::new())
+    }
+
+    pub fn query(deps: Deps, _env: Env, msg: PythQueryMsg) -> StdResult<Binary> {
+        match msg {
+            PythQueryMsg::PriceFeed {
+                ..
+            } => to_json_binary(&PriceFeedResponse {
+                price_feed: PRICE_FEED.load(deps.storage)?,
+            }),
+            _ => Err(StdError::generic_err("Unsupported query")),
+        }
+    }
+}
+
+/// ATOM/USD priced with 8 decimal places, e.g. 1_050_000_000 is 10.5
+fn price_feed(price: i64, conf: u64, publish_time: u64) -> PriceFeed {
+    let price = Price {
+        price,
+        conf,
+        expo: -8,
+        publish_time: publish_time as i64,
+    };
+    PriceFeed::new(PriceIdentifier::from_hex(PRICE_FEED_ID).unwrap(), price, price)
+}
+
+fn setup() -> (MarketApp, Addr, Addr) {
+    let mut app = th_app(&["alice", "bob"], 1_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+
+    let pyth_code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+        mock_pyth::execute,
+        mock_pyth::instantiate,
+        mock_pyth::query,
+    )));
+    let pyth = app
+        .instantiate_contract(
+            pyth_code_id,
+            Addr::unchecked(ADMIN),
+            &price_feed(0, 0, 0),
+            &[],
+            "pyth",
+            None,
+        )
+        .unwrap();
+
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            id: "atom-usd".to_string(),
+            label: "ATOM/USD above 10".to_string(),
+            home_team: "Above".to_string(),
+            away_team: "Below".to_string(),
+            market_type: Some(MarketType::PRICE_FEED {
+                pyth_contract: pyth.clone(),
+                price_feed_id: PRICE_FEED_ID.to_string(),
+                strike: Decimal::from_ratio(10_u128, 1_u128),
+                max_staleness: 60,
+                max_confidence_bps: 100,
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 100).unwrap();
+
+    // Move past the target time of the market
+    app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 + 10));
+
+    (app, pyth, market)
+}
+
+fn publish(app: &mut MarketApp, pyth: &Addr, price: i64, conf: u64, publish_time: u64) {
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        pyth.clone(),
+        &price_feed(price, conf, publish_time),
+        &[],
+    )
+    .unwrap();
+}
+
+fn score(app: &mut MarketApp, market: &Addr) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked("anyone"),
+        market.clone(),
+        &ExecuteMsg::Score {
+            result: None,
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn price_above_strike_settles_home() {
+    let (mut app, pyth, market) = setup();
+    let now = app.block_info().time.seconds();
+
+    publish(&mut app, &pyth, 1_050_000_000, 100_000, now);
+    score(&mut app, &market).unwrap();
+
+    let market_state = th_query_market(&app, &market);
+    assert_eq!(market_state.status, Status::CLOSED);
+    assert_eq!(market_state.result, Some(MarketResult::HOME));
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), 1_100);
+}
+
+#[test]
+fn price_at_strike_settles_away() {
+    let (mut app, pyth, market) = setup();
+    let now = app.block_info().time.seconds();
+
+    publish(&mut app, &pyth, 1_000_000_000, 0, now);
+    score(&mut app, &market).unwrap();
+
+    assert_eq!(th_query_market(&app, &market).result, Some(MarketResult::AWAY));
+}
+
+#[test]
+fn price_after_target_window_is_rejected() {
+    let (mut app, pyth, market) = setup();
+    let start_timestamp = th_query_market(&app, &market).start_timestamp;
+
+    publish(&mut app, &pyth, 1_050_000_000, 0, start_timestamp + 61);
+    let err = score(&mut app, &market).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PriceNotAvailable {});
+
+    // A price fresh relative to the block time is still outside the target window
+    app.update_block(|block| block.time = block.time.plus_seconds(2 * 60 * 60));
+    let now = app.block_info().time.seconds();
+
+    publish(&mut app, &pyth, 1_050_000_000, 0, now);
+    let err = score(&mut app, &market).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PriceNotAvailable {});
+}
+
+#[test]
+fn price_in_target_window_settles_late() {
+    let (mut app, pyth, market) = setup();
+    let start_timestamp = th_query_market(&app, &market).start_timestamp;
+
+    publish(&mut app, &pyth, 1_050_000_000, 0, start_timestamp + 60);
+    app.update_block(|block| block.time = block.time.plus_seconds(2 * 60 * 60));
+    score(&mut app, &market).unwrap();
+
+    assert_eq!(th_query_market(&app, &market).result, Some(MarketResult::HOME));
+}
+
+#[test]
+fn price_before_target_time_is_rejected() {
+    let (mut app, pyth, market) = setup();
+    let start_timestamp = th_query_market(&app, &market).start_timestamp;
+
+    publish(&mut app, &pyth, 1_050_000_000, 0, start_timestamp - 1);
+    let err = score(&mut app, &market).unwrap_err();
+
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PriceNotAvailable {});
+}
+
+#[test]
+fn wide_confidence_interval_is_rejected() {
+    let (mut app, pyth, market) = setup();
+    let now = app.block_info().time.seconds();
+
+    // 1.1% of the price, above the 1% bound
+    publish(&mut app, &pyth, 1_000_000_000, 11_000_000, now);
+    let err = score(&mut app, &market).unwrap_err();
+
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PriceNotConfident {});
+}
+
+#[test]
+fn market_without_price_is_force_cancelled_after_the_deadline() {
+    let (mut app, pyth, market) = setup();
+    let start_timestamp = th_query_market(&app, &market).start_timestamp;
+
+    // The only price is published after the target window, nor can the admin settle it
+    publish(&mut app, &pyth, 1_050_000_000, 0, start_timestamp + 61);
+    let err = score(&mut app, &market).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PriceNotAvailable {});
+    let err = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            market.clone(),
+            &ExecuteMsg::Score {
+                result: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PriceNotAvailable {});
+
+    let force_cancel = |app: &mut MarketApp| {
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            market.clone(),
+            &ExecuteMsg::ForceCancel {},
+            &[],
+        )
+    };
+
+    th_set_time(&mut app, start_timestamp + default_resolution_deadline() - 1);
+    let err = force_cancel(&mut app).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::DeadlineNotReached {});
+
+    th_set_time(&mut app, start_timestamp + default_resolution_deadline());
+    force_cancel(&mut app).unwrap();
+    assert_eq!(th_query_market(&app, &market).status, Status::CANCELLED);
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    th_claim_winnings(&mut app, &market, "bob").unwrap();
+    assert_eq!(th_balance(&app, "alice"), 1_000);
+    assert_eq!(th_balance(&app, "bob"), 1_000);
+}
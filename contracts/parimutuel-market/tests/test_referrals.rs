@@ -0,0 +1,186 @@
+use cosmwasm_std::{coins, Addr, Uint128};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{FeeModel, MarketResult},
+    ReferralInfo,
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_instantiate, th_instantiate_msg, th_query_market, th_score, th_set_time,
+    MarketApp, DENOM, TREASURY,
+};
+
+fn setup() -> (MarketApp, Addr) {
+    setup_with_fee_model(FeeModel::GROSS_POOL)
+}
+
+fn setup_with_fee_model(fee_model: FeeModel) -> (MarketApp, Addr) {
+    let mut app = th_app(&["alice", "bob", "carol"], 10_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            fee_bps: 500,
+            fee_model: Some(fee_model),
+            referral_share_bps: Some(2000),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    (app, market)
+}
+
+fn place_bet(
+    app: &mut MarketApp,
+    market: &Addr,
+    user: &str,
+    result: MarketResult,
+    receiver: Option<&str>,
+    referrer: Option<&str>,
+    amount: u128,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(user),
+        market.clone(),
+        &ExecuteMsg::PlaceBet {
+            result,
+            receiver: receiver.map(Addr::unchecked),
+            referrer: referrer.map(Addr::unchecked),
+        },
+        &coins(amount, DENOM),
+    )
+    .map(|_| ())
+}
+
+fn query_referral(app: &MarketApp, market: &Addr, referrer: &str) -> ReferralInfo {
+    app.wrap()
+        .query_wasm_smart(
+            market,
+            &QueryMsg::Referral {
+                referrer: Addr::unchecked(referrer),
+            },
+        )
+        .unwrap()
+}
+
+fn referred_volume(app: &MarketApp, market: &Addr, referrer: &str) -> u128 {
+    query_referral(app, market, referrer).referred_volume
+}
+
+/// Places a referred winning bet, an unreferred winning bet and a referred
+/// losing bet, then scores the market and claims the referral rewards
+fn score_and_claim_rewards(app: &mut MarketApp, market: &Addr) -> u128 {
+    place_bet(app, market, "alice", MarketResult::HOME, None, Some("dave"), 1_000).unwrap();
+    place_bet(app, market, "bob", MarketResult::HOME, None, None, 1_000).unwrap();
+    place_bet(app, market, "carol", MarketResult::AWAY, None, Some("dave"), 2_000).unwrap();
+
+    let start_timestamp = th_query_market(app, market).start_timestamp;
+    th_set_time(app, start_timestamp + 30 * 60);
+    th_score(app, market, MarketResult::HOME).unwrap();
+
+    let pending_rewards = query_referral(app, market, "dave").pending_rewards;
+    app.execute_contract(
+        Addr::unchecked("dave"),
+        market.clone(),
+        &ExecuteMsg::ClaimReferralRewards {},
+        &[],
+    )
+    .unwrap();
+    assert_eq!(th_balance(app, "dave"), pending_rewards);
+
+    pending_rewards
+}
+
+#[test]
+fn first_referrer_given_is_kept() {
+    let (mut app, market) = setup();
+
+    place_bet(&mut app, &market, "alice", MarketResult::HOME, None, None, 100).unwrap();
+    place_bet(&mut app, &market, "alice", MarketResult::HOME, None, Some("carol"), 200).unwrap();
+    place_bet(&mut app, &market, "alice", MarketResult::HOME, None, Some("dave"), 300).unwrap();
+    place_bet(&mut app, &market, "alice", MarketResult::HOME, None, None, 400).unwrap();
+
+    assert_eq!(referred_volume(&app, &market, "carol"), 200 + 300 + 400);
+    assert_eq!(referred_volume(&app, &market, "dave"), 0);
+}
+
+#[test]
+fn self_referral_is_rejected() {
+    let (mut app, market) = setup();
+
+    let err = place_bet(&mut app, &market, "alice", MarketResult::HOME, None, Some("alice"), 100)
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidReferrer {});
+
+    // Placing a bet for someone else does not make the sender its referrer
+    let err =
+        place_bet(&mut app, &market, "alice", MarketResult::HOME, Some("bob"), Some("alice"), 100)
+            .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidReferrer {});
+    let err =
+        place_bet(&mut app, &market, "alice", MarketResult::HOME, Some("bob"), Some("bob"), 100)
+            .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidReferrer {});
+
+    place_bet(&mut app, &market, "alice", MarketResult::HOME, Some("bob"), Some("carol"), 100)
+        .unwrap();
+    assert_eq!(referred_volume(&app, &market, "carol"), 100);
+}
+
+#[test]
+fn gross_pool_rewards_are_on_every_referred_stake() {
+    let (mut app, market) = setup_with_fee_model(FeeModel::GROSS_POOL);
+
+    // 5% of the 4000 pool, of which the referred 3000 make up 150
+    assert_eq!(score_and_claim_rewards(&mut app, &market), 30);
+}
+
+#[test]
+fn losing_pool_rewards_are_on_referred_losing_stakes() {
+    let (mut app, market) = setup_with_fee_model(FeeModel::LOSING_POOL);
+
+    // 5% of the 2000 losing pool, all of it referred
+    assert_eq!(score_and_claim_rewards(&mut app, &market), 20);
+}
+
+#[test]
+fn profit_only_rewards_are_on_referred_winning_stakes() {
+    let (mut app, market) = setup_with_fee_model(FeeModel::PROFIT_ONLY);
+
+    // 5% of the 2000 profit, half of it on the referred winning stake
+    assert_eq!(score_and_claim_rewards(&mut app, &market), 10);
+}
+
+#[test]
+fn flat_fee_rewards_are_held_per_referred_bet() {
+    let (mut app, market) = setup_with_fee_model(FeeModel::FLAT_PER_BET {
+        amount: Uint128::new(100),
+    });
+
+    place_bet(&mut app, &market, "alice", MarketResult::HOME, None, Some("dave"), 1_000).unwrap();
+    place_bet(&mut app, &market, "bob", MarketResult::HOME, None, None, 1_000).unwrap();
+
+    // The referrer's share is held in the market, the treasury gets the rest
+    assert_eq!(query_referral(&app, &market, "dave").pending_rewards, 20);
+    assert_eq!(th_balance(&app, TREASURY), 80 + 100);
+
+    // Rewards are only paid out once the market is resolved
+    let err = app
+        .execute_contract(
+            Addr::unchecked("dave"),
+            market.clone(),
+            &ExecuteMsg::ClaimReferralRewards {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketNotClosed {});
+
+    assert_eq!(score_and_claim_rewards(&mut app, &market), 20 + 20 + 20);
+    assert_eq!(th_balance(&app, TREASURY), 80 + 100 + 80 + 100 + 80);
+}
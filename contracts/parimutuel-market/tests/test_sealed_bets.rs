@@ -0,0 +1,209 @@
+use cosmwasm_std::{coins, Addr};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{MarketResult, SealedBets, UnrevealedPolicy},
+    SealedBet, TotalBets,
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_score,
+    th_set_time, MarketApp, ADMIN, DENOM, TREASURY,
+};
+
+fn setup(unrevealed: UnrevealedPolicy) -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&["alice", "bob", "carol"], 1_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            sealed_bets: Some(SealedBets {
+                reveal_window: 10 * 60,
+                unrevealed,
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn commit(app: &mut MarketApp, market: &Addr, user: &str, result: MarketResult, amount: u128) {
+    let commitment = SealedBet {
+        bettor: user.to_string(),
+        result,
+        salt: format!("{user}-salt"),
+    }
+    .commitment()
+    .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked(user),
+        market.clone(),
+        &ExecuteMsg::CommitBet {
+            commitment,
+        },
+        &coins(amount, DENOM),
+    )
+    .unwrap();
+}
+
+fn reveal(
+    app: &mut MarketApp,
+    market: &Addr,
+    user: &str,
+    result: MarketResult,
+    salt: &str,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(user),
+        market.clone(),
+        &ExecuteMsg::RevealBet {
+            result,
+            salt: salt.to_string(),
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn claim_commitments(app: &mut MarketApp, market: &Addr, user: &str) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(user),
+        market.clone(),
+        &ExecuteMsg::ClaimCommitments {},
+        &[],
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn only_revealed_bets_join_the_pools() {
+    let (mut app, market, start_timestamp) = setup(UnrevealedPolicy::FORFEIT);
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("alice"),
+            market.clone(),
+            &ExecuteMsg::PlaceBet {
+                result: MarketResult::HOME,
+                receiver: None,
+                referrer: None,
+            },
+            &coins(100, DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::BetsMustBeSealed {});
+
+    commit(&mut app, &market, "alice", MarketResult::HOME, 100);
+
+    let totals: TotalBets = app.wrap().query_wasm_smart(&market, &QueryMsg::Bets {}).unwrap();
+    assert_eq!(totals.total_home, 0);
+
+    // Nothing can be revealed before the cutoff
+    let err = reveal(&mut app, &market, "alice", MarketResult::HOME, "alice-salt").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::RevealWindowNotOpen {});
+
+    th_set_time(&mut app, start_timestamp);
+
+    let err = reveal(&mut app, &market, "alice", MarketResult::AWAY, "alice-salt").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::CommitmentNotFound {});
+    let err = reveal(&mut app, &market, "bob", MarketResult::HOME, "alice-salt").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::CommitmentNotFound {});
+
+    reveal(&mut app, &market, "alice", MarketResult::HOME, "alice-salt").unwrap();
+
+    let totals: TotalBets = app.wrap().query_wasm_smart(&market, &QueryMsg::Bets {}).unwrap();
+    assert_eq!(totals.total_home, 100);
+}
+
+#[test]
+fn unrevealed_bets_are_refunded_less_the_penalty() {
+    let (mut app, market, start_timestamp) = setup(UnrevealedPolicy::REFUND {
+        penalty_bps: 1000,
+    });
+
+    commit(&mut app, &market, "alice", MarketResult::HOME, 100);
+    commit(&mut app, &market, "bob", MarketResult::AWAY, 200);
+
+    th_set_time(&mut app, start_timestamp);
+    reveal(&mut app, &market, "alice", MarketResult::HOME, "alice-salt").unwrap();
+
+    let err = claim_commitments(&mut app, &market, "bob").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::RevealWindowOpen {});
+
+    th_set_time(&mut app, start_timestamp + 5 * 60 + 1);
+    claim_commitments(&mut app, &market, "bob").unwrap();
+
+    assert_eq!(th_balance(&app, "bob"), 1_000 - 20);
+    assert_eq!(th_balance(&app, TREASURY), 20);
+
+    let err = claim_commitments(&mut app, &market, "bob").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NoWinnings {});
+}
+
+#[test]
+fn unrevealed_bets_are_forfeited_to_the_winners() {
+    let (mut app, market, start_timestamp) = setup(UnrevealedPolicy::FORFEIT);
+
+    commit(&mut app, &market, "alice", MarketResult::HOME, 100);
+    commit(&mut app, &market, "bob", MarketResult::AWAY, 100);
+    commit(&mut app, &market, "carol", MarketResult::AWAY, 300);
+
+    th_set_time(&mut app, start_timestamp);
+    reveal(&mut app, &market, "alice", MarketResult::HOME, "alice-salt").unwrap();
+    reveal(&mut app, &market, "bob", MarketResult::AWAY, "bob-salt").unwrap();
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    let err = claim_commitments(&mut app, &market, "carol").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NoWinnings {});
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+
+    assert_eq!(th_balance(&app, "alice"), 1_000 + 100 + 300);
+    assert_eq!(th_balance(&app, market.as_str()), 0);
+}
+
+#[test]
+fn unrevealed_bets_are_refunded_in_full_on_cancellation() {
+    let (mut app, market, _) = setup(UnrevealedPolicy::FORFEIT);
+
+    commit(&mut app, &market, "alice", MarketResult::HOME, 100);
+
+    app.execute_contract(Addr::unchecked(ADMIN), market.clone(), &ExecuteMsg::Cancel {}, &[])
+        .unwrap();
+
+    claim_commitments(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), 1_000);
+}
+
+#[test]
+fn committed_bets_lock_the_denom_and_fee_increases() {
+    let (mut app, market, _) = setup(UnrevealedPolicy::FORFEIT);
+
+    commit(&mut app, &market, "alice", MarketResult::HOME, 100);
+
+    for msg in [
+        ExecuteMsg::UpdateConfig {
+            fee_bps: Some(100),
+            treasury_addr: None,
+            denom: None,
+        },
+        ExecuteMsg::UpdateConfig {
+            fee_bps: None,
+            treasury_addr: None,
+            denom: Some("uatom".to_string()),
+        },
+    ] {
+        let err =
+            app.execute_contract(Addr::unchecked(ADMIN), market.clone(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketHasBets {});
+    }
+}
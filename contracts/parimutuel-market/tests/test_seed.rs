@@ -0,0 +1,114 @@
+use cosmwasm_std::{coins, Addr, Uint128};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg},
+    state::{HouseSeed, MarketResult, SeedPolicy},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_score, th_set_time, MarketApp, ADMIN, DENOM, TREASURY,
+};
+
+const SEED: u128 = 1_000;
+const BALANCE: u128 = 10_000;
+
+/// Moneyline market seeded on both outcomes
+fn setup(policy: SeedPolicy) -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&[ADMIN, "alice", "bob"], BALANCE);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            seed: Some(HouseSeed {
+                amount: Uint128::new(SEED),
+                policy,
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &coins(2 * SEED, DENOM),
+    )
+    .unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn score(app: &mut MarketApp, market: &Addr, start_timestamp: u64, result: MarketResult) {
+    th_set_time(app, start_timestamp + 30 * 60);
+    th_score(app, market, result).unwrap();
+}
+
+#[test]
+fn redistributed_seed_goes_to_the_winners() {
+    let (mut app, market, start_timestamp) = setup(SeedPolicy::REDISTRIBUTE);
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 1_000).unwrap();
+    score(&mut app, &market, start_timestamp, MarketResult::HOME);
+
+    // The only winning bettor takes the whole pool, seed included
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 1_000 + 2 * SEED + 2_000);
+    assert_eq!(th_balance(&app, TREASURY), 0);
+    assert_eq!(th_balance(&app, market.as_str()), 0);
+}
+
+#[test]
+fn returned_seed_shares_the_winnings_with_the_house() {
+    let (mut app, market, start_timestamp) = setup(SeedPolicy::RETURN);
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 1_000).unwrap();
+    score(&mut app, &market, start_timestamp, MarketResult::HOME);
+
+    // The seed on the winning outcome wins like any other stake of the same size
+    assert_eq!(th_balance(&app, TREASURY), 2_000);
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 1_000 + 2_000);
+    assert_eq!(th_balance(&app, market.as_str()), 0);
+}
+
+#[test]
+fn seed_is_returned_on_cancel() {
+    for policy in [SeedPolicy::REDISTRIBUTE, SeedPolicy::RETURN] {
+        let (mut app, market, _) = setup(policy);
+
+        th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+        app.execute_contract(Addr::unchecked(ADMIN), market.clone(), &ExecuteMsg::Cancel {}, &[])
+            .unwrap();
+
+        assert_eq!(th_balance(&app, TREASURY), 2 * SEED);
+        th_claim_winnings(&mut app, &market, "alice").unwrap();
+        assert_eq!(th_balance(&app, "alice"), BALANCE);
+        assert_eq!(th_balance(&app, market.as_str()), 0);
+    }
+}
+
+#[test]
+fn seed_wins_an_outcome_nobody_bet_on() {
+    for policy in [SeedPolicy::REDISTRIBUTE, SeedPolicy::RETURN] {
+        let (mut app, market, start_timestamp) = setup(policy);
+
+        th_place_bet(&mut app, &market, "alice", MarketResult::AWAY, 1_000).unwrap();
+        score(&mut app, &market, start_timestamp, MarketResult::HOME);
+
+        // With no bettors to redistribute it to, the house takes the pool
+        assert_eq!(th_balance(&app, TREASURY), 2 * SEED + 1_000);
+        let err = th_claim_winnings(&mut app, &market, "alice").unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NoWinnings {});
+        assert_eq!(th_balance(&app, market.as_str()), 0);
+    }
+}
+
+#[test]
+fn seed_is_returned_when_nobody_bets() {
+    let (mut app, market, start_timestamp) = setup(SeedPolicy::REDISTRIBUTE);
+
+    score(&mut app, &market, start_timestamp, MarketResult::HOME);
+
+    assert_eq!(th_balance(&app, TREASURY), 2 * SEED);
+    assert_eq!(th_balance(&app, market.as_str()), 0);
+}
@@ -0,0 +1,205 @@
+// Tokenized positions change hands through the bank module instead
+#![cfg(not(feature = "tokenfactory"))]
+
+use std::str::FromStr;
+
+use cosmwasm_std::{coins, Addr, Decimal, Uint128};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::{ExecuteMsg, QueryMsg},
+    state::{MarketResult, SellOrder},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_score, th_set_time, MarketApp, DENOM,
+};
+
+const BALANCE: u128 = 10_000;
+
+/// Market where alice has 1000 on HOME and bob 1000 on AWAY
+fn setup() -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&["alice", "bob", "carol"], BALANCE);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(&mut app, &th_instantiate_msg(start_timestamp), &[]).unwrap();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 1_000).unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn create_order(
+    app: &mut MarketApp,
+    market: &Addr,
+    seller: &str,
+    amount: u128,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(seller),
+        market.clone(),
+        &ExecuteMsg::CreateSellOrder {
+            result: MarketResult::HOME,
+            amount: Uint128::new(amount),
+            price: Decimal::from_str("1.5").unwrap(),
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn fill_order(
+    app: &mut MarketApp,
+    market: &Addr,
+    order_id: u64,
+    amount: u128,
+    payment: u128,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked("carol"),
+        market.clone(),
+        &ExecuteMsg::FillSellOrder {
+            order_id,
+            amount: Uint128::new(amount),
+        },
+        &coins(payment, DENOM),
+    )
+    .map(|_| ())
+}
+
+fn cancel_order(
+    app: &mut MarketApp,
+    market: &Addr,
+    sender: &str,
+    order_id: u64,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(sender),
+        market.clone(),
+        &ExecuteMsg::CancelSellOrder {
+            order_id,
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn query_order(app: &MarketApp, market: &Addr, order_id: u64) -> Option<SellOrder> {
+    app.wrap()
+        .query_wasm_smart(
+            market,
+            &QueryMsg::SellOrder {
+                order_id,
+            },
+        )
+        .ok()
+}
+
+fn assert_error(err: anyhow::Error, expected: ContractError) {
+    assert_eq!(err.downcast::<ContractError>().unwrap(), expected);
+}
+
+#[test]
+fn orders_fill_in_parts() {
+    let (mut app, market, start_timestamp) = setup();
+
+    create_order(&mut app, &market, "alice", 600).unwrap();
+
+    // Whatever is paid above the price comes back
+    fill_order(&mut app, &market, 1, 200, 350).unwrap();
+    assert_eq!(query_order(&app, &market, 1).unwrap().amount, 400);
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 1_000 + 300);
+    assert_eq!(th_balance(&app, "carol"), BALANCE - 300);
+
+    assert_error(
+        fill_order(&mut app, &market, 1, 401, 602).unwrap_err(),
+        ContractError::InvalidAmount {},
+    );
+    assert_error(
+        fill_order(&mut app, &market, 1, 400, 599).unwrap_err(),
+        ContractError::PaymentError {},
+    );
+
+    // A fully filled order is gone
+    fill_order(&mut app, &market, 1, 400, 600).unwrap();
+    assert_eq!(query_order(&app, &market, 1), None);
+    assert_error(
+        fill_order(&mut app, &market, 1, 1, 2).unwrap_err(),
+        ContractError::SellOrderNotFound {},
+    );
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    th_claim_winnings(&mut app, &market, "carol").unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 1_000 + 900 + 800);
+    assert_eq!(th_balance(&app, "carol"), BALANCE - 900 + 1_200);
+}
+
+#[test]
+fn cancelling_an_order_frees_what_is_still_listed() {
+    let (mut app, market, _) = setup();
+
+    create_order(&mut app, &market, "alice", 600).unwrap();
+    assert_error(
+        create_order(&mut app, &market, "alice", 401).unwrap_err(),
+        ContractError::InsufficientPosition {},
+    );
+    assert_error(
+        cancel_order(&mut app, &market, "bob", 1).unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+
+    fill_order(&mut app, &market, 1, 200, 300).unwrap();
+    cancel_order(&mut app, &market, "alice", 1).unwrap();
+    assert_eq!(query_order(&app, &market, 1), None);
+    assert_error(
+        cancel_order(&mut app, &market, "alice", 1).unwrap_err(),
+        ContractError::SellOrderNotFound {},
+    );
+
+    // Everything that was not sold can be listed again
+    assert_error(
+        create_order(&mut app, &market, "alice", 801).unwrap_err(),
+        ContractError::InsufficientPosition {},
+    );
+    create_order(&mut app, &market, "alice", 800).unwrap();
+}
+
+#[test]
+fn listed_stake_is_held_until_the_claim() {
+    let (mut app, market, start_timestamp) = setup();
+
+    create_order(&mut app, &market, "alice", 600).unwrap();
+
+    // Listed stake cannot move any other way
+    let err = app
+        .execute_contract(
+            Addr::unchecked("alice"),
+            market.clone(),
+            &ExecuteMsg::TransferPosition {
+                outcome: MarketResult::HOME,
+                amount: Uint128::new(401),
+                recipient: Addr::unchecked("carol"),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_error(err, ContractError::InsufficientPosition {});
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    // An order left open is not filled once scored, the seller is paid for it
+    assert_error(
+        fill_order(&mut app, &market, 1, 600, 900).unwrap_err(),
+        ContractError::MarketNotActive {},
+    );
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 1_000 + 2_000);
+    assert_eq!(th_balance(&app, market.as_str()), 0);
+}
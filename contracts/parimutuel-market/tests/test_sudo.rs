@@ -0,0 +1,216 @@
+use cosmwasm_std::Addr;
+use cw_multi_test::AppResponse;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError,
+    msg::SudoMsg,
+    state::{MarketResult, Status},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_query_market, th_score, th_set_time, MarketApp,
+};
+
+fn setup(users: &[&str]) -> (MarketApp, Addr, u64) {
+    let mut app = th_app(users, 1_000);
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(&mut app, &th_instantiate_msg(start_timestamp), &[]).unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn sudo(app: &mut MarketApp, market: &Addr, msg: SudoMsg) -> AppResponse {
+    app.wasm_sudo(market.clone(), &msg).unwrap()
+}
+
+fn attribute(res: &AppResponse, key: &str) -> Option<String> {
+    res.events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == key)
+        .map(|attribute| attribute.value.clone())
+}
+
+#[test]
+fn betting_is_locked_once_the_cutoff_is_reached() {
+    let (mut app, market, start_timestamp) = setup(&["alice"]);
+
+    let res = sudo(&mut app, &market, SudoMsg::LockBetting {});
+    assert_eq!(attribute(&res, "action"), None);
+    assert_eq!(th_query_market(&app, &market).status, Status::ACTIVE);
+
+    th_set_time(&mut app, start_timestamp - 5 * 60);
+    let res = sudo(&mut app, &market, SudoMsg::LockBetting {});
+    assert_eq!(attribute(&res, "action"), Some("lock_betting".to_string()));
+    assert_eq!(th_query_market(&app, &market).status, Status::LOCKED);
+
+    // Running it again changes nothing
+    let res = sudo(&mut app, &market, SudoMsg::LockBetting {});
+    assert_eq!(attribute(&res, "action"), None);
+    assert_eq!(th_query_market(&app, &market).status, Status::LOCKED);
+}
+
+#[test]
+fn overdue_market_is_cancelled_once() {
+    let (mut app, market, start_timestamp) = setup(&["alice"]);
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+
+    // Unscored, but still within the default resolution deadline of a week
+    th_set_time(&mut app, start_timestamp + 7 * 24 * 60 * 60 - 1);
+    let res = sudo(&mut app, &market, SudoMsg::CancelOverdue {});
+    assert_eq!(attribute(&res, "action"), None);
+    assert_eq!(th_query_market(&app, &market).status, Status::ACTIVE);
+
+    th_set_time(&mut app, start_timestamp + 7 * 24 * 60 * 60);
+    sudo(&mut app, &market, SudoMsg::CancelOverdue {});
+    assert_eq!(th_query_market(&app, &market).status, Status::CANCELLED);
+
+    let res = sudo(&mut app, &market, SudoMsg::CancelOverdue {});
+    assert_eq!(attribute(&res, "action"), None);
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), 1_000);
+}
+
+#[test]
+fn scored_market_is_not_cancelled_when_overdue() {
+    let (mut app, market, start_timestamp) = setup(&["alice", "bob"]);
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 100).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 60 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 7 * 24 * 60 * 60);
+    sudo(&mut app, &market, SudoMsg::CancelOverdue {});
+    assert_eq!(th_query_market(&app, &market).status, Status::CLOSED);
+}
+
+// Tokenized positions are paid to whoever holds them, so they are never distributed
+#[cfg(not(feature = "tokenfactory"))]
+#[test]
+fn payouts_are_distributed_in_batches() {
+    let users = ["alice", "bob", "carol", "dave", "erin"];
+    let (mut app, market, start_timestamp) = setup(&users);
+    for user in ["alice", "bob", "carol", "dave"] {
+        th_place_bet(&mut app, &market, user, MarketResult::HOME, 100).unwrap();
+    }
+    th_place_bet(&mut app, &market, "erin", MarketResult::AWAY, 400).unwrap();
+
+    // Nothing is paid out before the market is scored
+    let res = sudo(
+        &mut app,
+        &market,
+        SudoMsg::DistributePayouts {
+            limit: 10,
+        },
+    );
+    assert_eq!(attribute(&res, "processed"), None);
+
+    th_set_time(&mut app, start_timestamp + 60 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    // Bob claims on his own before his turn comes
+    th_claim_winnings(&mut app, &market, "bob").unwrap();
+
+    let res = sudo(
+        &mut app,
+        &market,
+        SudoMsg::DistributePayouts {
+            limit: 2,
+        },
+    );
+    assert_eq!(attribute(&res, "processed"), Some("2".to_string()));
+    assert_eq!(th_balance(&app, "alice"), 1_100);
+    assert_eq!(th_balance(&app, "bob"), 1_100);
+    assert_eq!(th_balance(&app, "carol"), 900);
+
+    // The next batch picks up after bob, erin lost and is owed nothing
+    let res = sudo(
+        &mut app,
+        &market,
+        SudoMsg::DistributePayouts {
+            limit: 2,
+        },
+    );
+    assert_eq!(attribute(&res, "processed"), Some("2".to_string()));
+    assert_eq!(th_balance(&app, "carol"), 1_100);
+    assert_eq!(th_balance(&app, "dave"), 1_100);
+
+    let res = sudo(
+        &mut app,
+        &market,
+        SudoMsg::DistributePayouts {
+            limit: 2,
+        },
+    );
+    assert_eq!(attribute(&res, "processed"), Some("1".to_string()));
+    assert_eq!(th_balance(&app, "erin"), 600);
+
+    // Everyone was paid, so it is a no-op from here on
+    let res = sudo(
+        &mut app,
+        &market,
+        SudoMsg::DistributePayouts {
+            limit: 2,
+        },
+    );
+    assert_eq!(attribute(&res, "processed"), Some("0".to_string()));
+    assert_eq!(th_balance(&app, "alice"), 1_100);
+
+    // Paid addresses cannot claim again, while the loser was never marked as claimed
+    let err = th_claim_winnings(&mut app, &market, "alice").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ClaimAlreadyMade {});
+    let err = th_claim_winnings(&mut app, &market, "erin").unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NoWinnings {});
+}
+
+#[cfg(not(feature = "tokenfactory"))]
+#[test]
+fn distribution_batches_are_capped() {
+    let users: Vec<String> = (0..35).map(|i| format!("user{:02}", i)).collect();
+    let users: Vec<&str> = users.iter().map(String::as_str).collect();
+    let (mut app, market, start_timestamp) = setup(&users);
+    for (i, user) in users.iter().enumerate() {
+        let result = if i % 2 == 0 {
+            MarketResult::HOME
+        } else {
+            MarketResult::AWAY
+        };
+        th_place_bet(&mut app, &market, user, result, 100).unwrap();
+    }
+
+    th_set_time(&mut app, start_timestamp + 60 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    let res = sudo(
+        &mut app,
+        &market,
+        SudoMsg::DistributePayouts {
+            limit: u32::MAX,
+        },
+    );
+    assert_eq!(attribute(&res, "processed"), Some("30".to_string()));
+}
+
+#[cfg(feature = "tokenfactory")]
+#[test]
+fn tokenized_positions_are_not_distributed() {
+    let (mut app, market, start_timestamp) = setup(&["alice", "bob"]);
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 100).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 60 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+
+    let err = app
+        .wasm_sudo(
+            market.clone(),
+            &SudoMsg::DistributePayouts {
+                limit: 10,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PositionsTokenized {});
+}
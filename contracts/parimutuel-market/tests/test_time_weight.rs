@@ -0,0 +1,117 @@
+// Time weights are rejected when positions are tokenized
+#![cfg(not(feature = "tokenfactory"))]
+
+use cosmwasm_std::{Addr, Decimal};
+use proptest::prelude::*;
+use vendetta_markets_parimutuel_market::{
+    msg::InstantiateMsg,
+    state::{MarketResult, TimeWeight},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_score, th_set_time, MarketApp,
+};
+
+const USERS: [&str; 4] = ["alice", "bob", "carol", "dave"];
+const BALANCE: u128 = 1_000_000_000;
+const DECAY_PERIOD: u64 = 60 * 60;
+
+/// Market whose betting cutoff is two decay periods away
+fn setup(max_bonus: Decimal) -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&USERS, BALANCE);
+
+    let start_timestamp = app.block_info().time.seconds() + 2 * DECAY_PERIOD + 5 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            fee_bps: 250,
+            time_weight: Some(TimeWeight {
+                max_bonus,
+                decay_period: DECAY_PERIOD,
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn weighted_stake(app: &mut MarketApp, market: &Addr, user: &str, amount: u128) -> String {
+    let response = th_place_bet(app, market, user, MarketResult::HOME, amount).unwrap();
+    let event = response.events.iter().find(|event| event.ty == "wasm").unwrap();
+    let attribute = event.attributes.iter().find(|attribute| attribute.key == "weighted_stake");
+    attribute.unwrap().value.clone()
+}
+
+#[test]
+fn bets_at_the_cutoff_have_a_weight_of_one() {
+    let (mut app, market, start_timestamp) = setup(Decimal::percent(50));
+
+    th_set_time(&mut app, start_timestamp - 5 * 60);
+    assert_eq!(weighted_stake(&mut app, &market, "alice", 1_000), "1000");
+}
+
+#[test]
+fn bets_older_than_the_decay_period_get_the_full_bonus() {
+    let (mut app, market, start_timestamp) = setup(Decimal::percent(50));
+
+    assert_eq!(weighted_stake(&mut app, &market, "alice", 1_000), "1500");
+
+    th_set_time(&mut app, start_timestamp - 5 * 60 - DECAY_PERIOD);
+    assert_eq!(weighted_stake(&mut app, &market, "bob", 1_000), "1500");
+
+    th_set_time(&mut app, start_timestamp - 5 * 60 - DECAY_PERIOD / 2);
+    assert_eq!(weighted_stake(&mut app, &market, "carol", 1_000), "1250");
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    /// However the stakes are weighted, the winners never take more than the pool
+    #[test]
+    fn payouts_stay_within_the_pool(
+        max_bonus in 1_u64..=300,
+        bets in prop::collection::vec(
+            (0_usize..USERS.len(), any::<bool>(), 1_u128..=100_000, 0_u64..=2 * DECAY_PERIOD),
+            2..20,
+        ),
+    ) {
+        let (mut app, market, start_timestamp) = setup(Decimal::percent(max_bonus));
+
+        // Both sides need a bet for the market to be scoreable
+        let mut bets = bets;
+        bets[0].1 = true;
+        bets[1].1 = false;
+
+        // Bets go in the order they are placed, each some time after the previous one
+        let cutoff = start_timestamp - 5 * 60;
+        let mut timestamp = app.block_info().time.seconds();
+        for (user, is_home, amount, delay) in bets.iter() {
+            timestamp = (timestamp + delay / bets.len() as u64).min(cutoff);
+            th_set_time(&mut app, timestamp);
+            let result = if *is_home { MarketResult::HOME } else { MarketResult::AWAY };
+            th_place_bet(&mut app, &market, USERS[*user], result, *amount).unwrap();
+        }
+        let pool = th_balance(&app, market.as_str());
+
+        th_set_time(&mut app, start_timestamp + 30 * 60);
+        th_score(&mut app, &market, MarketResult::HOME).unwrap();
+        let fee = pool - th_balance(&app, market.as_str());
+
+        let mut paid_out = 0;
+        for user in USERS {
+            let balance = th_balance(&app, user);
+            if th_claim_winnings(&mut app, &market, user).is_ok() {
+                paid_out += th_balance(&app, user) - balance;
+            }
+        }
+
+        prop_assert!(paid_out <= pool - fee);
+        // Only rounding is left behind, at most a unit per winner
+        prop_assert!(pool - fee - paid_out <= USERS.len() as u128);
+    }
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "tokenfactory")]
+
+use cosmwasm_std::{coin, coins, Addr};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{msg::ExecuteMsg, state::MarketResult};
+
+mod helpers;
+use helpers::{
+    th_app, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet, th_score,
+    MarketApp, ADMIN, DENOM,
+};
+
+const MARKET_ID: &str = "game-1";
+
+fn setup() -> (MarketApp, Addr) {
+    let mut app = th_app(&["alice", "bob", "carol"], 1_000);
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(&mut app, &th_instantiate_msg(start_timestamp), &[]).unwrap();
+
+    (app, market)
+}
+
+fn position_denom(market: &Addr, outcome: &str) -> String {
+    format!("factory/{}/{}/{}", market, MARKET_ID, outcome)
+}
+
+/// Scores the market once it is scoreable
+fn score(app: &mut MarketApp, market: &Addr, result: MarketResult) {
+    app.update_block(|block| block.time = block.time.plus_seconds(2 * 60 * 60));
+    th_score(app, market, result).unwrap();
+}
+
+fn balance(app: &MarketApp, user: &str, denom: &str) -> u128 {
+    app.wrap().query_balance(user, denom).unwrap().amount.u128()
+}
+
+#[test]
+fn place_bet_mints_position() {
+    let (mut app, market) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "alice", MarketResult::AWAY, 50).unwrap();
+
+    assert_eq!(balance(&app, "alice", &position_denom(&market, "home")), 100);
+    assert_eq!(balance(&app, "alice", &position_denom(&market, "away")), 50);
+    assert_eq!(balance(&app, "alice", DENOM), 850);
+}
+
+#[test]
+fn holder_of_transferred_position_claims() {
+    let (mut app, market) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 300).unwrap();
+
+    let home = position_denom(&market, "home");
+    app.send_tokens(Addr::unchecked("alice"), Addr::unchecked("carol"), &coins(100, &home))
+        .unwrap();
+
+    score(&mut app, &market, MarketResult::HOME);
+
+    app.execute_contract(
+        Addr::unchecked("carol"),
+        market.clone(),
+        &ExecuteMsg::ClaimWinnings {
+            receiver: None,
+        },
+        &coins(100, &home),
+    )
+    .unwrap();
+
+    assert_eq!(balance(&app, "carol", DENOM), 1_400);
+    assert_eq!(balance(&app, "carol", &home), 0);
+    assert_eq!(balance(&app, market.as_str(), &home), 0);
+
+    // Alice no longer holds the position, so there is nothing left to claim
+    th_claim_winnings(&mut app, &market, "alice").unwrap_err();
+}
+
+#[test]
+fn losing_position_has_no_winnings() {
+    let (mut app, market) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 300).unwrap();
+
+    score(&mut app, &market, MarketResult::HOME);
+
+    app.execute_contract(
+        Addr::unchecked("bob"),
+        market.clone(),
+        &ExecuteMsg::ClaimWinnings {
+            receiver: None,
+        },
+        &coins(300, position_denom(&market, "away")),
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn cancelled_market_refunds_positions() {
+    let (mut app, market) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    th_place_bet(&mut app, &market, "alice", MarketResult::AWAY, 200).unwrap();
+
+    app.execute_contract(Addr::unchecked(ADMIN), market.clone(), &ExecuteMsg::Cancel {}, &[])
+        .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked("alice"),
+        market.clone(),
+        &ExecuteMsg::ClaimWinnings {
+            receiver: None,
+        },
+        &[coin(100, position_denom(&market, "home")), coin(200, position_denom(&market, "away"))],
+    )
+    .unwrap();
+
+    assert_eq!(balance(&app, "alice", DENOM), 1_000);
+}
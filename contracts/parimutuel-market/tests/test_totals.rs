@@ -0,0 +1,57 @@
+use cosmwasm_std::{Addr, Decimal};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{Market, MarketResult, MarketType, ScoreResult},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_set_time, ADMIN, TREASURY,
+};
+
+#[test]
+fn exact_hit_on_a_whole_line_refunds_every_bet() {
+    let mut app = th_app(&["alice", "bob"], 1_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            fee_bps: 250,
+            market_type: Some(MarketType::TOTALS {
+                line: Decimal::from_ratio(3_u128, 1_u128),
+            }),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::OVER, 100).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::UNDER, 300).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 2 * 60 * 60);
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::Score {
+            result: Some(ScoreResult::Total {
+                total: 3,
+            }),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let market_state: Market = app.wrap().query_wasm_smart(&market, &QueryMsg::Market {}).unwrap();
+    assert_eq!(market_state.result, Some(MarketResult::PUSH));
+
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    th_claim_winnings(&mut app, &market, "bob").unwrap();
+
+    assert_eq!(th_balance(&app, "alice"), 1_000);
+    assert_eq!(th_balance(&app, "bob"), 1_000);
+    assert_eq!(th_balance(&app, TREASURY), 0);
+}
@@ -0,0 +1,180 @@
+#[cfg(not(feature = "tokenfactory"))]
+use cosmwasm_std::{coins, Decimal};
+use cosmwasm_std::{Addr, Uint128};
+use cw_multi_test::Executor;
+use vendetta_markets_parimutuel_market::{
+    error::ContractError, msg::ExecuteMsg, state::MarketResult,
+};
+#[cfg(not(feature = "tokenfactory"))]
+use vendetta_markets_parimutuel_market::{msg::InstantiateMsg, state::MarketType, OutcomeOdds};
+
+mod helpers;
+use helpers::{th_app, th_instantiate, th_instantiate_msg, th_place_bet, MarketApp, ADMIN};
+#[cfg(not(feature = "tokenfactory"))]
+use helpers::{th_balance, th_claim_winnings, th_score, th_set_time, DENOM};
+
+const BALANCE: u128 = 10_000;
+
+fn setup() -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&[ADMIN, "alice", "bob", "carol"], BALANCE);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(&mut app, &th_instantiate_msg(start_timestamp), &[]).unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn transfer(
+    app: &mut MarketApp,
+    market: &Addr,
+    sender: &str,
+    recipient: &str,
+    amount: u128,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(sender),
+        market.clone(),
+        &ExecuteMsg::TransferPosition {
+            outcome: MarketResult::HOME,
+            amount: Uint128::new(amount),
+            recipient: Addr::unchecked(recipient),
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn set_transfers_disabled(
+    app: &mut MarketApp,
+    market: &Addr,
+    sender: &str,
+    disabled: bool,
+) -> anyhow::Result<()> {
+    app.execute_contract(
+        Addr::unchecked(sender),
+        market.clone(),
+        &ExecuteMsg::SetTransfersDisabled {
+            disabled,
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn assert_error(err: anyhow::Error, expected: ContractError) {
+    assert_eq!(err.downcast::<ContractError>().unwrap(), expected);
+}
+
+#[test]
+fn disabled_transfers_are_rejected_before_anything_else() {
+    let (mut app, market, _) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+
+    assert_error(
+        set_transfers_disabled(&mut app, &market, "alice", true).unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    set_transfers_disabled(&mut app, &market, ADMIN, true).unwrap();
+
+    // Even to an address that could never receive a position
+    for recipient in ["carol", ADMIN] {
+        assert_error(
+            transfer(&mut app, &market, "alice", recipient, 100).unwrap_err(),
+            ContractError::TransfersDisabled {},
+        );
+    }
+}
+
+#[cfg(not(feature = "tokenfactory"))]
+#[test]
+fn transfers_resume_once_enabled() {
+    let (mut app, market, _) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    set_transfers_disabled(&mut app, &market, ADMIN, true).unwrap();
+    transfer(&mut app, &market, "alice", "carol", 100).unwrap_err();
+
+    set_transfers_disabled(&mut app, &market, ADMIN, false).unwrap();
+    transfer(&mut app, &market, "alice", "carol", 100).unwrap();
+}
+
+#[cfg(not(feature = "tokenfactory"))]
+#[test]
+fn claimed_positions_do_not_move() {
+    let (mut app, market, start_timestamp) = setup();
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 1_000).unwrap();
+    transfer(&mut app, &market, "alice", "carol", 400).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 1_000 + 1_200);
+
+    // Neither out of a claimed position nor into one
+    assert_error(
+        transfer(&mut app, &market, "alice", "carol", 100).unwrap_err(),
+        ContractError::ClaimAlreadyMade {},
+    );
+    assert_error(
+        transfer(&mut app, &market, "carol", "alice", 100).unwrap_err(),
+        ContractError::ClaimAlreadyMade {},
+    );
+
+    th_claim_winnings(&mut app, &market, "carol").unwrap();
+    assert_eq!(th_balance(&app, "carol"), BALANCE + 800);
+    assert_eq!(th_balance(&app, market.as_str()), 0);
+}
+
+#[cfg(not(feature = "tokenfactory"))]
+#[test]
+fn fixed_odds_payouts_move_with_the_stake() {
+    let mut app = th_app(&[ADMIN, "alice", "carol"], BALANCE);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            market_type: Some(MarketType::FIXED_ODDS),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        market.clone(),
+        &ExecuteMsg::DepositLiquidity {},
+        &coins(2_000, DENOM),
+    )
+    .unwrap();
+
+    // Stake locked in at 2.0 and at 3.0, for a payout of 2500 on 1000
+    for odds in [2_u64, 3] {
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            market.clone(),
+            &ExecuteMsg::UpdateOdds {
+                odds: vec![OutcomeOdds {
+                    result: MarketResult::HOME,
+                    odds: Decimal::from_ratio(odds, 1_u64),
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+        th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 500).unwrap();
+    }
+
+    // The payout moves in proportion to the stake
+    transfer(&mut app, &market, "alice", "carol", 400).unwrap();
+
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+    th_claim_winnings(&mut app, &market, "alice").unwrap();
+    th_claim_winnings(&mut app, &market, "carol").unwrap();
+    assert_eq!(th_balance(&app, "alice"), BALANCE - 1_000 + 1_500);
+    assert_eq!(th_balance(&app, "carol"), BALANCE + 1_000);
+}
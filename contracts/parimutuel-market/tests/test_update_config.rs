@@ -0,0 +1,166 @@
+use cosmwasm_std::Addr;
+use cw_multi_test::{AppResponse, Executor};
+use vendetta_markets_parimutuel_market::{
+    contract::{FEE_TIMELOCK, MAX_FEE_BPS},
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    state::{Config, MarketResult, PendingFee},
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_instantiate, th_instantiate_msg, th_place_bet, th_score, th_set_time,
+    MarketApp, ADMIN, DENOM,
+};
+
+/// Market starting after the fee timelock, so a fee increase can still apply
+fn setup(fee_bps: u64) -> (MarketApp, Addr, u64) {
+    let mut app = th_app(&[ADMIN, "alice", "bob"], 10_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 2 * FEE_TIMELOCK;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            fee_bps,
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    (app, market, start_timestamp)
+}
+
+fn update_config(
+    app: &mut MarketApp,
+    market: &Addr,
+    sender: &str,
+    fee_bps: Option<u64>,
+    treasury_addr: Option<&str>,
+    denom: Option<&str>,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        Addr::unchecked(sender),
+        market.clone(),
+        &ExecuteMsg::UpdateConfig {
+            fee_bps,
+            treasury_addr: treasury_addr.map(str::to_string),
+            denom: denom.map(str::to_string),
+        },
+        &[],
+    )
+}
+
+fn query_config(app: &MarketApp, market: &Addr) -> Config {
+    app.wrap().query_wasm_smart(market, &QueryMsg::Config {}).unwrap()
+}
+
+fn query_pending_fee(app: &MarketApp, market: &Addr) -> Option<PendingFee> {
+    app.wrap().query_wasm_smart(market, &QueryMsg::PendingFee {}).unwrap()
+}
+
+fn attribute<'a>(response: &'a AppResponse, key: &str) -> Option<&'a str> {
+    let event = response.events.iter().find(|event| event.ty == "wasm").unwrap();
+    let attribute = event.attributes.iter().find(|attribute| attribute.key == key);
+    attribute.map(|attribute| attribute.value.as_str())
+}
+
+#[test]
+fn fee_above_the_maximum_is_rejected() {
+    let mut app = th_app(&[ADMIN], 10_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let err = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            fee_bps: MAX_FEE_BPS + 1,
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidFee(MAX_FEE_BPS));
+
+    let (mut app, market, _) = setup(0);
+    let err =
+        update_config(&mut app, &market, ADMIN, Some(MAX_FEE_BPS + 1), None, None).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidFee(MAX_FEE_BPS));
+}
+
+#[test]
+fn fee_increase_applies_to_the_first_bet_after_the_timelock() {
+    let (mut app, market, _) = setup(100);
+
+    update_config(&mut app, &market, ADMIN, Some(200), None, None).unwrap();
+    let pending_fee = query_pending_fee(&app, &market).unwrap();
+    assert_eq!(pending_fee.fee_bps, 200);
+    assert_eq!(query_config(&app, &market).fee_bps, 100);
+
+    th_set_time(&mut app, pending_fee.effective_at);
+    let response = th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    assert_eq!(attribute(&response, "fee_bps"), Some("200"));
+    assert_eq!(attribute(&response, "dropped_fee_bps"), None);
+
+    assert_eq!(query_config(&app, &market).fee_bps, 200);
+    assert_eq!(query_pending_fee(&app, &market), None);
+}
+
+#[test]
+fn fee_increase_is_dropped_by_a_bet_before_the_timelock() {
+    let (mut app, market, _) = setup(100);
+
+    update_config(&mut app, &market, ADMIN, Some(200), None, None).unwrap();
+
+    let response = th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 100).unwrap();
+    assert_eq!(attribute(&response, "dropped_fee_bps"), Some("200"));
+    assert_eq!(attribute(&response, "fee_bps"), None);
+
+    assert_eq!(query_config(&app, &market).fee_bps, 100);
+    assert_eq!(query_pending_fee(&app, &market), None);
+
+    // Only a decrease can still go through, and it applies right away
+    let err = update_config(&mut app, &market, ADMIN, Some(200), None, None).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketHasBets {});
+    update_config(&mut app, &market, ADMIN, Some(50), None, None).unwrap();
+    assert_eq!(query_config(&app, &market).fee_bps, 50);
+}
+
+#[test]
+fn fee_decrease_replaces_a_pending_increase() {
+    let (mut app, market, _) = setup(100);
+
+    update_config(&mut app, &market, ADMIN, Some(200), None, None).unwrap();
+    update_config(&mut app, &market, ADMIN, Some(50), None, None).unwrap();
+
+    assert_eq!(query_config(&app, &market).fee_bps, 50);
+    assert_eq!(query_pending_fee(&app, &market), None);
+}
+
+#[test]
+fn treasury_and_denom_are_updated() {
+    let (mut app, market, start_timestamp) = setup(500);
+
+    let err = update_config(&mut app, &market, "alice", None, Some("alice"), None).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {});
+
+    update_config(&mut app, &market, ADMIN, None, Some("new_treasury"), Some("uatom")).unwrap();
+    let config = query_config(&app, &market);
+    assert_eq!(config.treasury_addr, Addr::unchecked("new_treasury"));
+    assert_eq!(config.denom, "uatom");
+
+    // Bets are now taken in the new denom
+    let err = th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PaymentError {});
+    update_config(&mut app, &market, ADMIN, None, None, Some(DENOM)).unwrap();
+
+    // The fee goes to the new treasury
+    th_place_bet(&mut app, &market, "alice", MarketResult::HOME, 1_000).unwrap();
+    th_place_bet(&mut app, &market, "bob", MarketResult::AWAY, 1_000).unwrap();
+    th_set_time(&mut app, start_timestamp + 30 * 60);
+    th_score(&mut app, &market, MarketResult::HOME).unwrap();
+    assert_eq!(th_balance(&app, "new_treasury"), 100);
+
+    // The denom is locked once there are bets
+    let err = update_config(&mut app, &market, ADMIN, None, None, Some("uatom")).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MarketHasBets {});
+}
@@ -0,0 +1,105 @@
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use proptest::prelude::*;
+use vendetta_markets_parimutuel_market::{
+    msg::{InstantiateMsg, QueryMsg},
+    state::{MarketResult, VirtualWeight},
+    OutcomeOdds,
+};
+
+mod helpers;
+use helpers::{
+    th_app, th_balance, th_claim_winnings, th_instantiate, th_instantiate_msg, th_place_bet,
+    th_score, MarketApp, TREASURY,
+};
+
+const USERS: [&str; 4] = ["alice", "bob", "carol", "dave"];
+
+fn setup(fee_bps: u64, virtual_home: u128, virtual_away: u128) -> (MarketApp, Addr) {
+    let mut app = th_app(&USERS, 1_000_000_000);
+
+    let start_timestamp = app.block_info().time.seconds() + 60 * 60;
+    let market = th_instantiate(
+        &mut app,
+        &InstantiateMsg {
+            fee_bps,
+            virtual_weights: Some(vec![
+                VirtualWeight {
+                    result: MarketResult::HOME,
+                    amount: Uint128::new(virtual_home),
+                },
+                VirtualWeight {
+                    result: MarketResult::AWAY,
+                    amount: Uint128::new(virtual_away),
+                },
+            ]),
+            ..th_instantiate_msg(start_timestamp)
+        },
+        &[],
+    )
+    .unwrap();
+
+    (app, market)
+}
+
+#[test]
+fn virtual_weights_shape_odds() {
+    let (mut app, market) = setup(0, 300, 100);
+
+    let odds: Vec<OutcomeOdds> = app.wrap().query_wasm_smart(&market, &QueryMsg::Odds {}).unwrap();
+    assert_eq!(odds[0].odds, Decimal::from_ratio(400_u128, 300_u128));
+    assert_eq!(odds[1].odds, Decimal::from_ratio(400_u128, 100_u128));
+
+    th_place_bet(&mut app, &market, "alice", MarketResult::AWAY, 100).unwrap();
+
+    let odds: Vec<OutcomeOdds> = app.wrap().query_wasm_smart(&market, &QueryMsg::Odds {}).unwrap();
+    assert_eq!(odds[0].odds, Decimal::from_ratio(500_u128, 300_u128));
+    assert_eq!(odds[1].odds, Decimal::from_ratio(500_u128, 200_u128));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    /// Whatever the virtual weights, every winner can claim and the contract
+    /// never pays out more than it holds, keeping at most rounding dust
+    #[test]
+    fn virtual_weights_keep_market_solvent(
+        fee_bps in 0_u64..=1_000,
+        virtual_home in 1_u128..=1_000_000,
+        virtual_away in 1_u128..=1_000_000,
+        bets in prop::collection::vec((0_usize..USERS.len(), any::<bool>(), 1_u128..=100_000), 2..20),
+        home_wins in any::<bool>(),
+    ) {
+        let (mut app, market) = setup(fee_bps, virtual_home, virtual_away);
+
+        // Both sides need a bet for the market to be scoreable
+        let mut bets = bets;
+        bets[0].1 = true;
+        bets[1].1 = false;
+
+        let mut total_bets = 0;
+        for (user, is_home, amount) in bets.iter() {
+            let result = if *is_home { MarketResult::HOME } else { MarketResult::AWAY };
+            th_place_bet(&mut app, &market, USERS[*user], result, *amount).unwrap();
+            total_bets += amount;
+        }
+
+        app.update_block(|block| block.time = block.time.plus_seconds(2 * 60 * 60));
+        let result = if home_wins { MarketResult::HOME } else { MarketResult::AWAY };
+        th_score(&mut app, &market, result).unwrap();
+
+        let treasury = th_balance(&app, TREASURY);
+        prop_assert!(treasury <= total_bets);
+
+        let mut total_payouts = 0;
+        for user in USERS {
+            let before = th_balance(&app, user);
+            let claim = th_claim_winnings(&mut app, &market, user);
+            if claim.is_ok() {
+                total_payouts += th_balance(&app, user) - before;
+            }
+        }
+
+        prop_assert_eq!(treasury + total_payouts + th_balance(&app, market.as_str()), total_bets);
+        prop_assert!(th_balance(&app, market.as_str()) < USERS.len() as u128);
+    }
+}